@@ -0,0 +1,309 @@
+//! Off-chain helpers for building escrow instructions and decoding escrow accounts.
+//!
+//! Builders only emit the fixed account list. Extra accounts required by a
+//! Token-2022 transfer hook can be appended to the returned instruction's
+//! `accounts`. A take moves both mints, so mint A's hook accounts come first
+//! and [`Take::hook_accounts_a`] says how many there are.
+//!
+//! Either mint can be [`NATIVE_MINT`], in which case that side is paid in
+//! plain lamports: the builders put the wallets (and the escrow PDA) in place
+//! of the token accounts and the system program in place of the token program.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::instructions::EscrowInstrctions;
+use crate::token::{NATIVE_MINT as NATIVE_MINT_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+pub use crate::events::{EscrowEvent, EventKind};
+pub use crate::state::{Escrow, EscrowV2};
+pub use pinocchio::error::ProgramError;
+
+pub const PROGRAM_ID: Pubkey = crate::ID;
+pub const TOKEN_PROGRAM: Pubkey = TOKEN_PROGRAM_ID;
+pub const TOKEN_2022_PROGRAM: Pubkey = TOKEN_2022_PROGRAM_ID;
+pub const ASSOCIATED_TOKEN_PROGRAM: Pubkey = pinocchio_associated_token_account::ID;
+pub const SYSTEM_PROGRAM: Pubkey = pinocchio_system::ID;
+/// Sentinel mint for native SOL.
+pub const NATIVE_MINT: Pubkey = NATIVE_MINT_ID;
+
+/// Escrow state PDA for `maker`'s offer `offer_id`, with its bump.
+pub fn find_escrow_address(maker: &Pubkey, offer_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), &offer_id.to_le_bytes()],
+        &PROGRAM_ID,
+    )
+}
+
+/// Associated token account of `owner` for `mint` under `token_program`.
+pub fn find_associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM,
+    )
+    .0
+}
+
+/// Vault holding the escrowed mint A, the escrow PDA's associated token account.
+/// Native SOL is held by the escrow PDA itself.
+pub fn find_vault_address(escrow: &Pubkey, mint_a: &Pubkey, token_program_a: &Pubkey) -> Pubkey {
+    holding_address(escrow, mint_a, token_program_a)
+}
+
+/// Bump of the vault from [`find_vault_address`], 0 for native SOL.
+pub fn find_vault_bump(escrow: &Pubkey, mint_a: &Pubkey, token_program_a: &Pubkey) -> u8 {
+    if *mint_a == NATIVE_MINT {
+        return 0;
+    }
+    Pubkey::find_program_address(
+        &[escrow.as_ref(), token_program_a.as_ref(), mint_a.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM,
+    )
+    .1
+}
+
+/// Account `owner` holds `mint` in: its associated token account, or the wallet itself for native SOL.
+fn holding_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    if *mint == NATIVE_MINT {
+        *owner
+    } else {
+        find_associated_token_address(owner, mint, token_program)
+    }
+}
+
+/// Program moving `mint`: `token_program`, or the system program for native SOL.
+fn mint_program(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    if *mint == NATIVE_MINT {
+        SYSTEM_PROGRAM
+    } else {
+        *token_program
+    }
+}
+
+/// Decodes a `Make` escrow account.
+pub fn decode_escrow(data: &[u8]) -> Result<Escrow, ProgramError> {
+    Escrow::from_bytes(data)
+}
+
+/// Decodes a `MakeV2` escrow account.
+pub fn decode_escrow_v2(data: &[u8]) -> Result<EscrowV2, ProgramError> {
+    EscrowV2::from_bytes(data)
+}
+
+/// Escrow events found in a transaction's log messages, in the order they were emitted.
+pub fn parse_events(logs: &[String]) -> Vec<EscrowEvent> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .flat_map(|fields| fields.split(' '))
+        .filter_map(|field| BASE64.decode(field).ok())
+        .filter_map(|data| EscrowEvent::from_bytes(&data).ok())
+        .collect()
+}
+
+/// `Make` / `MakeV2`: the maker deposits `amount_to_give` of mint A and asks
+/// for `amount_to_receive` of mint B.
+pub struct Make {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub offer_id: u64,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    /// Unix timestamp after which the offer can't be taken, 0 for never.
+    pub expiry: i64,
+    /// The only allowed taker, `Pubkey::default()` to leave the offer open.
+    pub taker: Pubkey,
+}
+
+impl Make {
+    pub fn instruction(&self) -> Instruction {
+        self.build(EscrowInstrctions::Make)
+    }
+
+    pub fn instruction_v2(&self) -> Instruction {
+        self.build(EscrowInstrctions::MakeV2)
+    }
+
+    fn build(&self, discriminator: EscrowInstrctions) -> Instruction {
+        let (escrow, bump) = find_escrow_address(&self.maker, self.offer_id);
+
+        let mut data = Vec::with_capacity(1 + crate::instructions::MakeArgs::LEN);
+        data.push(discriminator as u8);
+        data.push(bump);
+        data.extend_from_slice(&self.amount_to_receive.to_le_bytes());
+        data.extend_from_slice(&self.amount_to_give.to_le_bytes());
+        data.extend_from_slice(&self.offer_id.to_le_bytes());
+        data.extend_from_slice(&self.expiry.to_le_bytes());
+        data.extend_from_slice(self.taker.as_ref());
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new_readonly(self.mint_b, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(holding_address(&self.maker, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new(find_vault_address(&escrow, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+                AccountMeta::new_readonly(mint_program(&self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM, false),
+            ],
+            data,
+        }
+    }
+}
+
+/// `Take` / `TakeV2` / `TakePartial`: the taker pays mint B to the maker and
+/// receives the escrowed mint A.
+pub struct Take {
+    pub taker: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub offer_id: u64,
+    /// How many of the appended transfer hook accounts belong to mint A, the rest belong to mint B.
+    pub hook_accounts_a: u8,
+}
+
+impl Take {
+    pub fn instruction(&self) -> Instruction {
+        self.build(EscrowInstrctions::Take, &[])
+    }
+
+    pub fn instruction_v2(&self) -> Instruction {
+        self.build(EscrowInstrctions::TakeV2, &[])
+    }
+
+    /// Fills the offer for `amount_b` of mint B, in exchange for a proportional share of mint A.
+    pub fn partial_instruction(&self, amount_b: u64) -> Instruction {
+        self.build(EscrowInstrctions::TakePartial, &amount_b.to_le_bytes())
+    }
+
+    fn build(&self, discriminator: EscrowInstrctions, args: &[u8]) -> Instruction {
+        let (escrow, _) = find_escrow_address(&self.maker, self.offer_id);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.taker, true),
+                AccountMeta::new(self.maker, false),
+                AccountMeta::new(self.mint_a, false),
+                AccountMeta::new_readonly(self.mint_b, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(holding_address(&self.taker, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new(holding_address(&self.taker, &self.mint_b, &self.token_program_b), false),
+                AccountMeta::new(holding_address(&self.maker, &self.mint_b, &self.token_program_b), false),
+                AccountMeta::new(find_vault_address(&escrow, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(mint_program(&self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(mint_program(&self.mint_b, &self.token_program_b), false),
+            ],
+            data: [&[discriminator as u8][..], args, &[self.hook_accounts_a]].concat(),
+        }
+    }
+}
+
+/// `Cancel` / `CancelV2`: the maker withdraws the deposit and closes the offer.
+pub struct Cancel {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub token_program_a: Pubkey,
+    pub offer_id: u64,
+}
+
+impl Cancel {
+    pub fn instruction(&self) -> Instruction {
+        self.build(EscrowInstrctions::Cancel)
+    }
+
+    pub fn instruction_v2(&self) -> Instruction {
+        self.build(EscrowInstrctions::CancelV2)
+    }
+
+    fn build(&self, discriminator: EscrowInstrctions) -> Instruction {
+        let (escrow, _) = find_escrow_address(&self.maker, self.offer_id);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new(self.mint_a, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(find_vault_address(&escrow, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new(holding_address(&self.maker, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+                AccountMeta::new_readonly(mint_program(&self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM, false),
+            ],
+            data: vec![discriminator as u8],
+        }
+    }
+}
+
+/// `Expire`: refunds an expired offer to the maker. Needs no signature beyond the fee payer.
+pub struct Expire {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub token_program_a: Pubkey,
+    pub offer_id: u64,
+}
+
+impl Expire {
+    pub fn instruction(&self) -> Instruction {
+        let (escrow, _) = find_escrow_address(&self.maker, self.offer_id);
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.maker, false),
+                AccountMeta::new(self.mint_a, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(find_vault_address(&escrow, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new(holding_address(&self.maker, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(mint_program(&self.mint_a, &self.token_program_a), false),
+            ],
+            data: vec![EscrowInstrctions::Expire as u8],
+        }
+    }
+}
+
+/// `Amend`: the maker reprices the offer and optionally tops up or withdraws part of the deposit.
+pub struct Amend {
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub token_program_a: Pubkey,
+    pub offer_id: u64,
+    pub amount_to_receive: u64,
+    pub deposit: u64,
+    pub withdraw: u64,
+}
+
+impl Amend {
+    pub fn instruction(&self) -> Instruction {
+        let (escrow, _) = find_escrow_address(&self.maker, self.offer_id);
+
+        let mut data = Vec::with_capacity(1 + crate::instructions::AmendArgs::LEN);
+        data.push(EscrowInstrctions::Amend as u8);
+        data.extend_from_slice(&self.amount_to_receive.to_le_bytes());
+        data.extend_from_slice(&self.deposit.to_le_bytes());
+        data.extend_from_slice(&self.withdraw.to_le_bytes());
+        data.push(find_vault_bump(&escrow, &self.mint_a, &self.token_program_a));
+
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new_readonly(self.mint_a, false),
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(find_vault_address(&escrow, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new(holding_address(&self.maker, &self.mint_a, &self.token_program_a), false),
+                AccountMeta::new_readonly(mint_program(&self.mint_a, &self.token_program_a), false),
+            ],
+            data,
+        }
+    }
+}
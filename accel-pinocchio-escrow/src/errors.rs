@@ -0,0 +1,19 @@
+use pinocchio::error::ProgramError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EscrowError {
+    PdaMismatch = 0,
+    MintMismatch = 1,
+    InvalidOwner = 2,
+    AlreadyInitialized = 3,
+    MissingRequiredSignature = 4,
+    OfferExpired = 5,
+    OfferNotExpired = 6,
+    UnauthorizedTaker = 7,
+}
+
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        Self::Custom(e as u32)
+    }
+}
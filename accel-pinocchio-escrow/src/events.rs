@@ -0,0 +1,102 @@
+use pinocchio::{Address, error::ProgramError};
+
+/// Bumped whenever the event layout changes, so indexers can tell formats apart.
+pub const EVENT_VERSION: u8 = 1;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    Make = 0,
+    Take = 1,
+    Cancel = 2,
+    TakePartial = 3,
+    Expire = 4,
+    Amend = 5,
+}
+
+impl TryFrom<u8> for EventKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EventKind::Make),
+            1 => Ok(EventKind::Take),
+            2 => Ok(EventKind::Cancel),
+            3 => Ok(EventKind::TakePartial),
+            4 => Ok(EventKind::Expire),
+            5 => Ok(EventKind::Amend),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Emitted through `sol_log_data` by every state-changing instruction, laid out as
+/// `version (u8) | kind (u8) | escrow | maker | taker | mint_a | mint_b (32 bytes each) | amount_a (u64 LE) | amount_b (u64 LE)`.
+///
+/// - `Make` / `Amend`: `taker` is the designated taker (all zeroes if open), the amounts are the
+///   resulting offer, mint A on deposit and mint B asked for.
+/// - `Take` / `TakePartial`: the mint A released to `taker` and the mint B paid to the maker.
+/// - `Cancel` / `Expire`: `taker` is all zeroes, `amount_a` is the mint A refunded and `amount_b` is 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EscrowEvent {
+    pub kind: EventKind,
+    pub escrow: Address,
+    pub maker: Address,
+    pub taker: Address,
+    pub mint_a: Address,
+    pub mint_b: Address,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+impl EscrowEvent {
+    pub const LEN: usize = 1 + 1 + 32 * 5 + 8 + 8;
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0u8; Self::LEN];
+        data[0] = EVENT_VERSION;
+        data[1] = self.kind as u8;
+
+        let addresses = [&self.escrow, &self.maker, &self.taker, &self.mint_a, &self.mint_b];
+        for (i, address) in addresses.into_iter().enumerate() {
+            data[2 + 32 * i..2 + 32 * (i + 1)].copy_from_slice(address.as_ref());
+        }
+
+        data[162..170].copy_from_slice(&self.amount_a.to_le_bytes());
+        data[170..178].copy_from_slice(&self.amount_b.to_le_bytes());
+        data
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN || data[0] != EVENT_VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let address = |i: usize| Address::new_from_array(data[2 + 32 * i..2 + 32 * (i + 1)].try_into().unwrap());
+        let amount = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        Ok(Self {
+            kind: EventKind::try_from(data[1])?,
+            escrow: address(0),
+            maker: address(1),
+            taker: address(2),
+            mint_a: address(3),
+            mint_b: address(4),
+            amount_a: amount(162),
+            amount_b: amount(170),
+        })
+    }
+
+    pub fn emit(&self) {
+        let data = self.to_bytes();
+
+        #[cfg(any(target_os = "solana", target_arch = "bpf"))]
+        unsafe {
+            let fields: [&[u8]; 1] = [&data];
+            pinocchio::syscalls::sol_log_data(fields.as_ptr() as *const u8, fields.len() as u64);
+        }
+
+        #[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+        let _ = data;
+    }
+}
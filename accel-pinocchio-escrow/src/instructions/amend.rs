@@ -0,0 +1,209 @@
+use pinocchio::{
+    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError
+};
+use pinocchio_pubkey::derive_address;
+use pinocchio_system::instructions::Transfer;
+
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
+use crate::state::Escrow;
+use crate::token::{
+    check_mint_program, is_native_mint, mint_decimals, move_lamports, TokenAccountState, TransferChecked,
+};
+
+/// Instruction data for `Amend`:
+/// `amount_to_receive (u64 LE) | deposit (u64 LE) | withdraw (u64 LE) | vault_bump (u8)`.
+///
+/// At most one of `deposit` and `withdraw` may be non-zero. Withdrawing the
+/// whole deposit isn't allowed, that's what `Cancel` is for. `vault_bump` is the
+/// bump of the escrow's associated token account, ignored for native SOL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmendArgs {
+    pub amount_to_receive: u64,
+    pub deposit: u64,
+    pub withdraw: u64,
+    pub vault_bump: u8,
+}
+
+impl AmendArgs {
+    pub const LEN: usize = 8 + 8 + 8 + 1;
+}
+
+impl TryFrom<&[u8]> for AmendArgs {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let read_u64 = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            u64::from_le_bytes(bytes)
+        };
+
+        let args = Self {
+            amount_to_receive: read_u64(0),
+            deposit: read_u64(8),
+            withdraw: read_u64(16),
+            vault_bump: data[24],
+        };
+
+        if args.amount_to_receive == 0 || (args.deposit != 0 && args.withdraw != 0) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(args)
+    }
+}
+
+pub fn process_amend_instruction(
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+
+    let [
+        maker,
+        mint_a,
+        escrow_account,
+        escrow_ata,
+        maker_ata_a,
+        token_program,
+        transfer_hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+
+    let native_a = is_native_mint(mint_a);
+
+    check_mint_program(mint_a, token_program)?;
+
+    let AmendArgs { amount_to_receive, deposit, withdraw, vault_bump } = AmendArgs::try_from(data)?;
+
+    // Read escrow state and extract needed data, then drop the borrow
+    let (bump, maker_addr, offer_id, stored_mint_a, stored_mint_b, designated_taker, amount_to_give) = {
+        let escrow_state = Escrow::from_account_info_readonly(escrow_account)?;
+        (
+            escrow_state.bump,
+            escrow_state.maker(),
+            escrow_state.offer_id(),
+            escrow_state.mint_a(),
+            escrow_state.mint_b(),
+            escrow_state.taker(),
+            escrow_state.amount_to_give(),
+        )
+    };
+
+    if maker_addr != *maker.address() {
+        return Err(EscrowError::InvalidOwner.into());
+    }
+
+    if stored_mint_a != *mint_a.address() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
+
+    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
+
+    // The recorded amount is read back from the vault, so it has to be the escrow's own
+    // associated token account, the one Make created
+    if !native_a {
+        let vault_seed = [
+            escrow_account.address().as_ref(),
+            token_program.address().as_ref(),
+            mint_a.address().as_ref(),
+            &[vault_bump],
+        ];
+        let vault_pda = derive_address(&vault_seed, None, &pinocchio_associated_token_account::ID.to_bytes());
+        if vault_pda != *escrow_ata.address().as_array() {
+            return Err(EscrowError::PdaMismatch.into());
+        }
+
+        let escrow_ata_state = TokenAccountState::from_account_view(escrow_ata)?;
+        if escrow_ata_state.owner != *escrow_account.address() {
+            return Err(EscrowError::InvalidOwner.into());
+        }
+        if escrow_ata_state.mint != *mint_a.address() {
+            return Err(EscrowError::MintMismatch.into());
+        }
+    }
+
+    if withdraw >= amount_to_give {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if deposit > 0 && native_a {
+        Transfer {
+            from: maker,
+            to: escrow_account,
+            lamports: deposit,
+        }.invoke()?;
+    } else if deposit > 0 {
+        TransferChecked {
+            from: maker_ata_a,
+            mint: mint_a,
+            to: escrow_ata,
+            authority: maker,
+            token_program,
+            extra_accounts: transfer_hook_accounts,
+            amount: deposit,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke()?;
+    }
+
+    if withdraw > 0 && native_a {
+        move_lamports(escrow_account, maker, withdraw)?;
+    } else if withdraw > 0 {
+        let bump = [bump.to_le()];
+        let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
+        let seeds = Signer::from(&seed);
+
+        TransferChecked {
+            from: escrow_ata,
+            mint: mint_a,
+            to: maker_ata_a,
+            authority: escrow_account,
+            token_program,
+            extra_accounts: transfer_hook_accounts,
+            amount: withdraw,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke_signed(&[seeds])?;
+    }
+
+    // The vault always holds exactly what's on offer, net of any Token-2022 transfer fee
+    let amount_to_give = if native_a {
+        amount_to_give
+            .checked_add(deposit)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            - withdraw
+    } else {
+        TokenAccountState::from_account_view(escrow_ata)?.amount
+    };
+
+    let escrow_state = Escrow::from_account_info(escrow_account)?;
+    escrow_state.set_amount_to_receive(amount_to_receive);
+    escrow_state.set_amount_to_give(amount_to_give);
+
+    EscrowEvent {
+        kind: EventKind::Amend,
+        escrow: *escrow_account.address(),
+        maker: *maker.address(),
+        taker: designated_taker,
+        mint_a: *mint_a.address(),
+        mint_b: stored_mint_b,
+        amount_a: amount_to_give,
+        amount_b: amount_to_receive,
+    }.emit();
+
+    Ok(())
+}
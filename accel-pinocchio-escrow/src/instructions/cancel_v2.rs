@@ -0,0 +1,102 @@
+use pinocchio::{
+    AccountView, Address, ProgramResult, cpi::{Seed, Signer}, error::ProgramError
+};
+
+use pinocchio_pubkey::derive_address;
+
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
+use crate::state::{close_escrow_account, EscrowV2};
+use crate::token::{check_mint_program, close_vault, is_native_mint, mint_decimals, TransferChecked};
+
+pub fn process_cancel_instruction_v2(
+    accounts: &[AccountView],
+    _data: &[u8],
+) -> ProgramResult {
+
+    let [
+        maker,
+        mint_a,
+        escrow_account,
+        escrow_ata,
+        maker_ata_a,
+        _system_program,
+        token_program,
+        _associated_token_program,
+        transfer_hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+
+    let native_a = is_native_mint(mint_a);
+
+    check_mint_program(mint_a, token_program)?;
+
+    // Deserialize the escrow state through wincode
+    let escrow_state = EscrowV2::from_account_info(escrow_account)?;
+    let (bump, maker_addr, offer_id, stored_mint_a, stored_mint_b, amount_to_give) = (
+        escrow_state.bump,
+        escrow_state.maker(),
+        escrow_state.offer_id(),
+        escrow_state.mint_a(),
+        escrow_state.mint_b(),
+        escrow_state.amount_to_give(),
+    );
+
+    if maker_addr != *maker.address() {
+        return Err(EscrowError::InvalidOwner.into());
+    }
+
+    if stored_mint_a != *mint_a.address() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
+
+    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
+
+    let bump = [bump.to_le()];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
+    let seeds = Signer::from(&seed);
+
+    // A native deposit is refunded along with the escrow's own lamports
+    if !native_a {
+        // Transfer tokens from vault back to maker before closing
+        TransferChecked {
+            from: escrow_ata,
+            mint: mint_a,
+            to: maker_ata_a,
+            authority: escrow_account,
+            token_program,
+            extra_accounts: transfer_hook_accounts,
+            amount: amount_to_give,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke_signed(core::slice::from_ref(&seeds))?;
+
+        // Now close the vault account
+        close_vault(escrow_ata, mint_a, maker, escrow_account, token_program, &[seeds])?;
+    }
+
+    close_escrow_account(escrow_account, maker)?;
+
+    EscrowEvent {
+        kind: EventKind::Cancel,
+        escrow: *escrow_account.address(),
+        maker: *maker.address(),
+        taker: Address::default(),
+        mint_a: *mint_a.address(),
+        mint_b: stored_mint_b,
+        amount_a: amount_to_give,
+        amount_b: 0,
+    }.emit();
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use pinocchio::{
+    AccountView, Address, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, clock::Clock}
+};
+use pinocchio_pubkey::derive_address;
+
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
+use crate::state::{close_escrow_account, is_expired, Escrow};
+use crate::token::{check_mint_program, close_vault, is_native_mint, mint_decimals, TokenAccountState, TransferChecked};
+
+/// Permissionless refund of an expired offer: anyone can send mint A back to
+/// the maker and close the escrow, with all rent going to the maker.
+pub fn process_expire_instruction(
+    accounts: &[AccountView],
+    _data: &[u8],
+) -> ProgramResult {
+
+    let [
+        maker,
+        mint_a,
+        escrow_account,
+        escrow_ata,
+        maker_ata_a,
+        token_program,
+        transfer_hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let native_a = is_native_mint(mint_a);
+
+    check_mint_program(mint_a, token_program)?;
+
+    // The caller picks the destination, so make sure it really is the maker's account.
+    // A native refund always goes to the maker's wallet.
+    if !native_a {
+        let maker_ata_a_state = TokenAccountState::from_account_view(maker_ata_a)?;
+        if maker_ata_a_state.owner != *maker.address() {
+            return Err(EscrowError::InvalidOwner.into());
+        }
+        if maker_ata_a_state.mint != *mint_a.address() {
+            return Err(EscrowError::MintMismatch.into());
+        }
+    }
+
+    // Read escrow state and extract needed data, then drop the borrow
+    let (bump, maker_addr, offer_id, stored_mint_a, stored_mint_b, amount_to_give, expiry) = {
+        let escrow_state = Escrow::from_account_info_readonly(escrow_account)?;
+        (
+            escrow_state.bump,
+            escrow_state.maker(),
+            escrow_state.offer_id(),
+            escrow_state.mint_a(),
+            escrow_state.mint_b(),
+            escrow_state.amount_to_give(),
+            escrow_state.expiry(),
+        )
+    };
+
+    if maker_addr != *maker.address() {
+        return Err(EscrowError::InvalidOwner.into());
+    }
+
+    if stored_mint_a != *mint_a.address() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    if !is_expired(expiry, Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferNotExpired.into());
+    }
+
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
+
+    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
+
+    let bump = [bump.to_le()];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
+    let seeds = Signer::from(&seed);
+
+    // A native deposit is refunded along with the escrow's own lamports
+    if !native_a {
+        // Refund the deposit to the maker
+        TransferChecked {
+            from: escrow_ata,
+            mint: mint_a,
+            to: maker_ata_a,
+            authority: escrow_account,
+            token_program,
+            extra_accounts: transfer_hook_accounts,
+            amount: amount_to_give,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke_signed(core::slice::from_ref(&seeds))?;
+
+        close_vault(escrow_ata, mint_a, maker, escrow_account, token_program, &[seeds])?;
+    }
+
+    close_escrow_account(escrow_account, maker)?;
+
+    EscrowEvent {
+        kind: EventKind::Expire,
+        escrow: *escrow_account.address(),
+        maker: *maker.address(),
+        taker: Address::default(),
+        mint_a: *mint_a.address(),
+        mint_b: stored_mint_b,
+        amount_a: amount_to_give,
+        amount_b: 0,
+    }.emit();
+
+    Ok(())
+}
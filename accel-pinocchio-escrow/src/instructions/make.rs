@@ -1,10 +1,57 @@
 use pinocchio::{
-    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, rent::Rent}
+    AccountView, Address, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, rent::Rent}
 };
 use pinocchio_pubkey::derive_address;
-use pinocchio_system::instructions::CreateAccount;
+use pinocchio_system::instructions::{CreateAccount, Transfer};
 
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
 use crate::state::Escrow;
+use crate::token::{check_mint_program, is_native_mint, mint_decimals, TokenAccountState, TransferChecked};
+
+/// Instruction data shared by `Make` and `MakeV2`:
+/// `bump (u8) | amount_to_receive (u64 LE) | amount_to_give (u64 LE) | offer_id (u64 LE) | expiry (i64 LE) | taker (32 bytes)`.
+///
+/// `expiry` is a unix timestamp after which the offer can no longer be taken; 0 means it never expires.
+/// `taker` restricts the offer to a single counterparty; all zeroes leaves it open to anyone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MakeArgs {
+    pub bump: u8,
+    pub amount_to_receive: u64,
+    pub amount_to_give: u64,
+    pub offer_id: u64,
+    pub expiry: i64,
+    pub taker: [u8; 32],
+}
+
+impl MakeArgs {
+    pub const LEN: usize = 1 + 8 + 8 + 8 + 8 + 32;
+}
+
+impl TryFrom<&[u8]> for MakeArgs {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let read_bytes = |offset: usize| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            bytes
+        };
+
+        Ok(Self {
+            bump: data[0],
+            amount_to_receive: u64::from_le_bytes(read_bytes(1)),
+            amount_to_give: u64::from_le_bytes(read_bytes(9)),
+            offer_id: u64::from_le_bytes(read_bytes(17)),
+            expiry: i64::from_le_bytes(read_bytes(25)),
+            taker: data[33..65].try_into().unwrap(),
+        })
+    }
+}
 
 pub fn process_make_instruction(
     accounts: &[AccountView],
@@ -20,34 +67,45 @@ pub fn process_make_instruction(
         escrow_ata,
         system_program,
         token_program,
-        _associated_token_program@ ..
+        _associated_token_program,
+        transfer_hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Check maker_ata_a and extract needed data, then drop the borrow
-    {
-        let maker_ata_a_state = pinocchio_token::state::TokenAccount::from_account_view(&maker_ata_a)?;
-        if maker_ata_a_state.owner() != maker.address() {
-            return Err(ProgramError::IllegalOwner);
+    if !maker.is_signer() {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+
+    let args = MakeArgs::try_from(data)?;
+
+    let native_a = is_native_mint(mint_a);
+
+    check_mint_program(mint_a, token_program)?;
+
+    // Check maker_ata_a and drop the borrow, a native deposit comes straight from the maker
+    if !native_a {
+        let maker_ata_a_state = TokenAccountState::from_account_view(maker_ata_a)?;
+        if maker_ata_a_state.owner != *maker.address() {
+            return Err(EscrowError::InvalidOwner.into());
         }
-        if maker_ata_a_state.mint() != mint_a.address() {
-            return Err(ProgramError::InvalidAccountData);
+        if maker_ata_a_state.mint != *mint_a.address() {
+            return Err(EscrowError::MintMismatch.into());
         }
     }
 
-    let bump = data[0];
-    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &[bump]];
-    let seeds = &seed[..];
+    let MakeArgs { bump, amount_to_receive, amount_to_give, offer_id, expiry, taker } = args;
 
-    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(escrow_account_pda, *escrow_account.address().as_array());
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
 
-    let amount_to_receive = unsafe{ *(data.as_ptr().add(1) as *const u64) };
-    let amount_to_give = unsafe{ *(data.as_ptr().add(9) as *const u64) };
+    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
 
     let bump = [bump.to_le()];
-    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&bump)];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
     let seeds = Signer::from(&seed);
 
     unsafe {
@@ -58,39 +116,71 @@ pub fn process_make_instruction(
                 lamports: Rent::get()?.try_minimum_balance(Escrow::LEN)?,
                 space: Escrow::LEN as u64,
                 owner: &crate::ID,
-            }.invoke_signed(&[seeds.clone()])?;
+            }.invoke_signed(core::slice::from_ref(&seeds))?;
+
+            let amount_to_give = if native_a {
+                // Native SOL is held by the escrow PDA itself, on top of its rent
+                Transfer {
+                    from: maker,
+                    to: escrow_account,
+                    lamports: amount_to_give,
+                }.invoke()?;
+
+                amount_to_give
+            } else {
+                pinocchio_associated_token_account::instructions::Create {
+                    funding_account: maker,
+                    account: escrow_ata,
+                    wallet: escrow_account,
+                    mint: mint_a,
+                    token_program,
+                    system_program,
+                }.invoke()?;
+
+                TransferChecked {
+                    from: maker_ata_a,
+                    mint: mint_a,
+                    to: escrow_ata,
+                    authority: maker,
+                    token_program,
+                    extra_accounts: transfer_hook_accounts,
+                    amount: amount_to_give,
+                    decimals: mint_decimals(mint_a)?,
+                }.invoke()?;
+
+                // Record what actually landed in the vault, net of any Token-2022 transfer fee
+                TokenAccountState::from_account_view(escrow_ata)?.amount
+            };
 
             // Initialize escrow state
             {
                 let escrow_state = Escrow::from_account_info(escrow_account)?;
                 escrow_state.set_maker(maker.address());
+                escrow_state.set_offer_id(offer_id);
                 escrow_state.set_mint_a(mint_a.address());
                 escrow_state.set_mint_b(mint_b.address());
                 escrow_state.set_amount_to_receive(amount_to_receive);
                 escrow_state.set_amount_to_give(amount_to_give);
-                escrow_state.bump = data[0];
+                escrow_state.set_expiry(expiry);
+                escrow_state.set_taker(&Address::new_from_array(taker));
+                escrow_state.bump = args.bump;
             }
 
-            pinocchio_associated_token_account::instructions::Create {
-                funding_account: maker,
-                account: escrow_ata,
-                wallet: escrow_account,
-                mint: mint_a,
-                token_program: token_program,
-                system_program: system_program,
-            }.invoke()?;
-
-            pinocchio_token::instructions::Transfer {
-                from: maker_ata_a,
-                to: escrow_ata,
-                authority: maker,
-                amount: amount_to_give,
-            }.invoke()?;
+            EscrowEvent {
+                kind: EventKind::Make,
+                escrow: *escrow_account.address(),
+                maker: *maker.address(),
+                taker: Address::new_from_array(taker),
+                mint_a: *mint_a.address(),
+                mint_b: *mint_b.address(),
+                amount_a: amount_to_give,
+                amount_b: amount_to_receive,
+            }.emit();
 
             return Ok(());
         }
         else {
-            return Err(ProgramError::IllegalOwner);
+            return Err(EscrowError::AlreadyInitialized.into());
         }
     }
 }
\ No newline at end of file
@@ -2,11 +2,21 @@ pub mod make;
 pub mod make_v2;
 pub mod take;
 pub mod cancel;
+pub mod take_v2;
+pub mod cancel_v2;
+pub mod take_partial;
+pub mod expire;
+pub mod amend;
 
 pub use make::*;
 pub use make_v2::*;
 pub use take::*;
 pub use cancel::*;
+pub use take_v2::*;
+pub use cancel_v2::*;
+pub use take_partial::*;
+pub use expire::*;
+pub use amend::*;
 
 use pinocchio::error::ProgramError;
 
@@ -15,6 +25,11 @@ pub enum EscrowInstrctions {
     Take = 1,
     Cancel = 2,
     MakeV2 = 3,
+    TakeV2 = 4,
+    CancelV2 = 5,
+    TakePartial = 6,
+    Expire = 7,
+    Amend = 8,
 }
 
 impl TryFrom<&u8> for EscrowInstrctions {
@@ -26,6 +41,11 @@ impl TryFrom<&u8> for EscrowInstrctions {
             1 => Ok(EscrowInstrctions::Take),
             2 => Ok(EscrowInstrctions::Cancel),
             3 => Ok(EscrowInstrctions::MakeV2),
+            4 => Ok(EscrowInstrctions::TakeV2),
+            5 => Ok(EscrowInstrctions::CancelV2),
+            6 => Ok(EscrowInstrctions::TakePartial),
+            7 => Ok(EscrowInstrctions::Expire),
+            8 => Ok(EscrowInstrctions::Amend),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -1,15 +1,43 @@
 use pinocchio::{
-    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError
+    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, clock::Clock}
 };
 use pinocchio_pubkey::derive_address;
+use pinocchio_system::instructions::Transfer;
+
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
+use crate::state::{close_escrow_account, is_allowed_taker, is_expired, Escrow};
+use crate::token::{
+    check_mint_program, close_vault, is_native_mint, mint_decimals, move_lamports, split_hook_accounts,
+    TokenAccountState, TransferChecked,
+};
+
+/// Instruction data for `Take` and `TakeV2`: `hook_accounts_a (u8)`, how many
+/// of the trailing transfer hook accounts belong to mint A. The rest belong to mint B.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TakeArgs {
+    pub hook_accounts_a: u8,
+}
+
+impl TakeArgs {
+    pub const LEN: usize = 1;
+}
 
-use pinocchio_token::instructions::Transfer;
+impl TryFrom<&[u8]> for TakeArgs {
+    type Error = ProgramError;
 
-use crate::state::Escrow;
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let [hook_accounts_a]: [u8; Self::LEN] = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self { hook_accounts_a })
+    }
+}
 
 pub fn process_take_instruction(
     accounts: &[AccountView],
-    _data: &[u8],
+    data: &[u8],
 ) -> ProgramResult {
 
     let [
@@ -22,80 +50,143 @@ pub fn process_take_instruction(
         taker_ata_b,
         maker_ata_b,
         escrow_ata,
-        token_program,
-        _associated_token_program@ ..
+        token_program_a,
+        token_program_b,
+        transfer_hook_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
     if !taker.is_signer() {
-        return Err(ProgramError::MissingRequiredSignature);
+        return Err(EscrowError::MissingRequiredSignature.into());
     }
 
-    // Check maker_ata_b and drop the borrow
-    {
-        let maker_ata_b_state = pinocchio_token::state::TokenAccount::from_account_view(&maker_ata_b)?;
+    let native_a = is_native_mint(mint_a);
+    let native_b = is_native_mint(mint_b);
+
+    check_mint_program(mint_a, token_program_a)?;
+    check_mint_program(mint_b, token_program_b)?;
 
-        if maker_ata_b_state.owner() != maker.address() {
-            return Err(ProgramError::IllegalOwner);
+    let TakeArgs { hook_accounts_a } = TakeArgs::try_from(data)?;
+    let (hook_accounts_a, hook_accounts_b) = split_hook_accounts(transfer_hook_accounts, hook_accounts_a)?;
+
+    // Check maker_ata_b and drop the borrow, native SOL is paid straight to the maker
+    if !native_b {
+        let maker_ata_b_state = TokenAccountState::from_account_view(maker_ata_b)?;
+
+        if maker_ata_b_state.owner != *maker.address() {
+            return Err(EscrowError::InvalidOwner.into());
         }
-        if maker_ata_b_state.mint() != mint_b.address() {
-            return Err(ProgramError::InvalidAccountData);
+        if maker_ata_b_state.mint != *mint_b.address() {
+            return Err(EscrowError::MintMismatch.into());
         }
     }
 
-    // Check taker_ata_a and drop the borrow
-    {
-        let taker_ata_a_state = pinocchio_token::state::TokenAccount::from_account_view(&taker_ata_a)?;
+    // Check taker_ata_a and drop the borrow, native SOL is released straight to the taker
+    if !native_a {
+        let taker_ata_a_state = TokenAccountState::from_account_view(taker_ata_a)?;
 
-        if taker_ata_a_state.owner() != taker.address() {
-            return Err(ProgramError::IllegalOwner);
+        if taker_ata_a_state.owner != *taker.address() {
+            return Err(EscrowError::InvalidOwner.into());
         }
-        if taker_ata_a_state.mint() != mint_a.address() {
-            return Err(ProgramError::InvalidAccountData);
+        if taker_ata_a_state.mint != *mint_a.address() {
+            return Err(EscrowError::MintMismatch.into());
         }
     }
 
     // Read escrow state and extract needed data, then drop the borrow
-    let (bump, amount_to_receive, amount_to_give) = {
+    let (bump, offer_id, stored_mint_a, stored_mint_b, amount_to_receive, amount_to_give, expiry, designated_taker) = {
         let escrow_state = Escrow::from_account_info_readonly(&escrow_account)?;
-        (escrow_state.bump, escrow_state.amount_to_receive(), escrow_state.amount_to_give())
+        (
+            escrow_state.bump,
+            escrow_state.offer_id(),
+            escrow_state.mint_a(),
+            escrow_state.mint_b(),
+            escrow_state.amount_to_receive(),
+            escrow_state.amount_to_give(),
+            escrow_state.expiry(),
+            escrow_state.taker(),
+        )
     };
 
-    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &[bump]];
-    let seeds = &seed[..];
+    if stored_mint_a != *mint_a.address() || stored_mint_b != *mint_b.address() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    if is_expired(expiry, Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferExpired.into());
+    }
+
+    if !is_allowed_taker(&designated_taker, taker.address()) {
+        return Err(EscrowError::UnauthorizedTaker.into());
+    }
+
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
 
     let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
-    assert_eq!(escrow_account_pda, *escrow_account.address().as_array());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
 
 
     let bump = [bump.to_le()];
-    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&bump)];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
     let seeds = Signer::from(&seed);
-    {
-        Transfer {
+
+    // Release the deposited mint A to the taker
+    if native_a {
+        move_lamports(escrow_account, taker, amount_to_give)?;
+    } else {
+        TransferChecked {
             from: escrow_ata,
+            mint: mint_a,
             to: taker_ata_a,
             authority: escrow_account,
-            amount: amount_to_receive,
-        }.invoke_signed(&[seeds])?;
+            token_program: token_program_a,
+            extra_accounts: hook_accounts_a,
+            amount: amount_to_give,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke_signed(core::slice::from_ref(&seeds))?;
     }
 
-    {
+    // Pay the maker in mint B
+    if native_b {
         Transfer {
+            from: taker,
+            to: maker,
+            lamports: amount_to_receive,
+        }.invoke()?;
+    } else {
+        TransferChecked {
             from: taker_ata_b,
+            mint: mint_b,
             to: maker_ata_b,
             authority: taker,
-            amount: amount_to_give,
-        }.invoke()?
+            token_program: token_program_b,
+            extra_accounts: hook_accounts_b,
+            amount: amount_to_receive,
+            decimals: mint_decimals(mint_b)?,
+        }.invoke()?;
     }
 
-    // Update escrow state
-    {
-        let escrow_state = Escrow::from_account_info(&escrow_account)?;
-        escrow_state.set_amount_to_receive(0);
-        escrow_state.set_amount_to_give(0);
+    // Close the now empty vault, rent goes back to the maker
+    if !native_a {
+        close_vault(escrow_ata, mint_a, maker, escrow_account, token_program_a, &[seeds])?;
     }
 
+    close_escrow_account(escrow_account, maker)?;
+
+    EscrowEvent {
+        kind: EventKind::Take,
+        escrow: *escrow_account.address(),
+        maker: *maker.address(),
+        taker: *taker.address(),
+        mint_a: *mint_a.address(),
+        mint_b: *mint_b.address(),
+        amount_a: amount_to_give,
+        amount_b: amount_to_receive,
+    }.emit();
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,224 @@
+use pinocchio::{
+    AccountView, ProgramResult, cpi::{Seed, Signer}, error::ProgramError, sysvars::{Sysvar, clock::Clock}
+};
+use pinocchio_pubkey::derive_address;
+use pinocchio_system::instructions::Transfer;
+
+use crate::errors::EscrowError;
+use crate::events::{EscrowEvent, EventKind};
+use crate::state::{close_escrow_account, is_allowed_taker, is_expired, Escrow};
+use crate::token::{
+    check_mint_program, close_vault, is_native_mint, mint_decimals, move_lamports, split_hook_accounts,
+    TokenAccountState, TransferChecked,
+};
+
+/// Instruction data for `TakePartial`: `amount_b (u64 LE)`, the amount of
+/// mint B the taker pays in this fill, then `hook_accounts_a (u8)` as in `TakeArgs`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TakePartialArgs {
+    pub amount_b: u64,
+    pub hook_accounts_a: u8,
+}
+
+impl TakePartialArgs {
+    pub const LEN: usize = 9;
+}
+
+impl TryFrom<&[u8]> for TakePartialArgs {
+    type Error = ProgramError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; Self::LEN] = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        Ok(Self {
+            amount_b: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            hook_accounts_a: bytes[8],
+        })
+    }
+}
+
+pub fn process_take_partial_instruction(
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+
+    let [
+        taker,
+        maker,
+        mint_a,
+        mint_b,
+        escrow_account,
+        taker_ata_a,
+        taker_ata_b,
+        maker_ata_b,
+        escrow_ata,
+        token_program_a,
+        token_program_b,
+        transfer_hook_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !taker.is_signer() {
+        return Err(EscrowError::MissingRequiredSignature.into());
+    }
+
+    let native_a = is_native_mint(mint_a);
+    let native_b = is_native_mint(mint_b);
+
+    check_mint_program(mint_a, token_program_a)?;
+    check_mint_program(mint_b, token_program_b)?;
+
+    let TakePartialArgs { amount_b, hook_accounts_a } = TakePartialArgs::try_from(data)?;
+    let (hook_accounts_a, hook_accounts_b) = split_hook_accounts(transfer_hook_accounts, hook_accounts_a)?;
+
+    // Check maker_ata_b and drop the borrow, native SOL is paid straight to the maker
+    if !native_b {
+        let maker_ata_b_state = TokenAccountState::from_account_view(maker_ata_b)?;
+
+        if maker_ata_b_state.owner != *maker.address() {
+            return Err(EscrowError::InvalidOwner.into());
+        }
+        if maker_ata_b_state.mint != *mint_b.address() {
+            return Err(EscrowError::MintMismatch.into());
+        }
+    }
+
+    // Check taker_ata_a and drop the borrow, native SOL is released straight to the taker
+    if !native_a {
+        let taker_ata_a_state = TokenAccountState::from_account_view(taker_ata_a)?;
+
+        if taker_ata_a_state.owner != *taker.address() {
+            return Err(EscrowError::InvalidOwner.into());
+        }
+        if taker_ata_a_state.mint != *mint_a.address() {
+            return Err(EscrowError::MintMismatch.into());
+        }
+    }
+
+    // Read escrow state and extract needed data, then drop the borrow
+    let (bump, offer_id, stored_mint_a, stored_mint_b, amount_to_receive, amount_to_give, expiry, designated_taker) = {
+        let escrow_state = Escrow::from_account_info_readonly(escrow_account)?;
+        (
+            escrow_state.bump,
+            escrow_state.offer_id(),
+            escrow_state.mint_a(),
+            escrow_state.mint_b(),
+            escrow_state.amount_to_receive(),
+            escrow_state.amount_to_give(),
+            escrow_state.expiry(),
+            escrow_state.taker(),
+        )
+    };
+
+    if stored_mint_a != *mint_a.address() || stored_mint_b != *mint_b.address() {
+        return Err(EscrowError::MintMismatch.into());
+    }
+
+    if is_expired(expiry, Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferExpired.into());
+    }
+
+    if !is_allowed_taker(&designated_taker, taker.address()) {
+        return Err(EscrowError::UnauthorizedTaker.into());
+    }
+
+    let offer_id_bytes = offer_id.to_le_bytes();
+    let seed = [b"escrow".as_ref(), maker.address().as_ref(), &offer_id_bytes, &[bump]];
+
+    let escrow_account_pda = derive_address(&seed, None, &crate::ID.to_bytes());
+    if escrow_account_pda != *escrow_account.address().as_array() {
+        return Err(EscrowError::PdaMismatch.into());
+    }
+
+    let amount_a = fill_amount(amount_b, amount_to_receive, amount_to_give)?;
+    let fully_filled = amount_b == amount_to_receive;
+
+    let bump = [bump.to_le()];
+    let seed = [Seed::from(b"escrow"), Seed::from(maker.address().as_array()), Seed::from(&offer_id_bytes), Seed::from(&bump)];
+    let seeds = Signer::from(&seed);
+
+    // Release the proportional share of mint A to the taker
+    if native_a {
+        move_lamports(escrow_account, taker, amount_a)?;
+    } else {
+        TransferChecked {
+            from: escrow_ata,
+            mint: mint_a,
+            to: taker_ata_a,
+            authority: escrow_account,
+            token_program: token_program_a,
+            extra_accounts: hook_accounts_a,
+            amount: amount_a,
+            decimals: mint_decimals(mint_a)?,
+        }.invoke_signed(core::slice::from_ref(&seeds))?;
+    }
+
+    // Pay the maker in mint B
+    if native_b {
+        Transfer {
+            from: taker,
+            to: maker,
+            lamports: amount_b,
+        }.invoke()?;
+    } else {
+        TransferChecked {
+            from: taker_ata_b,
+            mint: mint_b,
+            to: maker_ata_b,
+            authority: taker,
+            token_program: token_program_b,
+            extra_accounts: hook_accounts_b,
+            amount: amount_b,
+            decimals: mint_decimals(mint_b)?,
+        }.invoke()?;
+    }
+
+    if fully_filled {
+        if !native_a {
+            close_vault(escrow_ata, mint_a, maker, escrow_account, token_program_a, &[seeds])?;
+        }
+
+        close_escrow_account(escrow_account, maker)?;
+    } else {
+        let escrow_state = Escrow::from_account_info(escrow_account)?;
+        escrow_state.set_amount_to_receive(amount_to_receive - amount_b);
+        escrow_state.set_amount_to_give(amount_to_give - amount_a);
+    }
+
+    EscrowEvent {
+        kind: EventKind::TakePartial,
+        escrow: *escrow_account.address(),
+        maker: *maker.address(),
+        taker: *taker.address(),
+        mint_a: *mint_a.address(),
+        mint_b: *mint_b.address(),
+        amount_a,
+        amount_b,
+    }.emit();
+
+    Ok(())
+}
+
+/// Amount of mint A released for `amount_b` of mint B at the escrow's remaining
+/// price. Rounds down so any dust stays with the maker; the fill that pays the
+/// rest of `amount_to_receive` takes whatever mint A is left in the vault.
+pub fn fill_amount(amount_b: u64, amount_to_receive: u64, amount_to_give: u64) -> Result<u64, ProgramError> {
+    if amount_b == 0 || amount_b > amount_to_receive {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if amount_b == amount_to_receive {
+        return Ok(amount_to_give);
+    }
+
+    let amount_a = (amount_b as u128 * amount_to_give as u128) / amount_to_receive as u128;
+    if amount_a == 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // amount_b < amount_to_receive, so amount_a < amount_to_give fits in a u64
+    Ok(amount_a as u64)
+}
@@ -1,13 +1,20 @@
 #![allow(unexpected_cfgs)]
-use pinocchio::{AccountView, entrypoint, Address, ProgramResult, address::declare_id, error::ProgramError};
+use pinocchio::{AccountView, Address, ProgramResult, address::declare_id, error::ProgramError};
 
 use crate::instructions::EscrowInstrctions;
 
 mod tests;
-mod state;
+pub mod errors;
+pub mod events;
+pub mod state;
 mod instructions;
+mod token;
 
-entrypoint!(process_instruction);
+#[cfg(any(test, feature = "client"))]
+pub mod client;
+
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
 
 declare_id!("4ibrEMW5F6hKnkW4jVedswYv6H6VtwPN6ar6dvXDN1nT");
 
@@ -17,7 +24,9 @@ pub fn process_instruction(
     instruction_data: &[u8],
 ) -> ProgramResult {
 
-    assert_eq!(program_id, &ID);
+    if program_id != &ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
 
     let (discriminator, data) = instruction_data.split_first()
         .ok_or(ProgramError::InvalidInstructionData)?;
@@ -27,7 +36,11 @@ pub fn process_instruction(
         EscrowInstrctions::Take => instructions::process_take_instruction(accounts, data)?,
         EscrowInstrctions::Cancel => instructions::process_cancel_instruction(accounts, data)?,
         EscrowInstrctions::MakeV2 => instructions::process_make_instruction_v2(accounts, data)?,
-        _ => return Err(ProgramError::InvalidInstructionData),
+        EscrowInstrctions::TakeV2 => instructions::process_take_instruction_v2(accounts, data)?,
+        EscrowInstrctions::CancelV2 => instructions::process_cancel_instruction_v2(accounts, data)?,
+        EscrowInstrctions::TakePartial => instructions::process_take_partial_instruction(accounts, data)?,
+        EscrowInstrctions::Expire => instructions::process_expire_instruction(accounts, data)?,
+        EscrowInstrctions::Amend => instructions::process_amend_instruction(accounts, data)?,
     }
     Ok(())
 }
\ No newline at end of file
@@ -1,13 +1,17 @@
-use pinocchio::{AccountView, error::ProgramError};
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
 use wincode::{SchemaRead, SchemaWrite};
 
+#[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Escrow {
     maker: [u8; 32],
+    offer_id: [u8; 8],
     mint_a: [u8; 32],
     mint_b: [u8; 32],
     amount_to_receive: [u8; 8],
     amount_to_give: [u8; 8],
+    expiry: [u8; 8],
+    taker: [u8; 32],
     pub bump: u8,
 }
 
@@ -15,17 +19,20 @@ pub struct Escrow {
 #[derive(SchemaRead, SchemaWrite)]
 pub struct EscrowV2 {
     maker: [u8; 32],
+    offer_id: [u8; 8],
     mint_a: [u8; 32],
     mint_b: [u8; 32],
     amount_to_receive: [u8; 8],
     amount_to_give: [u8; 8],
+    expiry: [u8; 8],
+    taker: [u8; 32],
     pub bump: u8,
 }
 
 
 
 impl Escrow  {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1;
+    pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 8 + 8 + 32 + 1;
 
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
         let mut data = account_info.try_borrow_mut()?;
@@ -53,6 +60,15 @@ impl Escrow  {
         Ok(unsafe { &*(data.as_ptr() as *const Self) })
     }
 
+    /// Decodes an escrow from raw account data, e.g. as fetched off-chain.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Escrow::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
     pub fn maker(&self) -> pinocchio::Address {
         pinocchio::Address::from(self.maker)
     }
@@ -61,6 +77,14 @@ impl Escrow  {
         self.maker.copy_from_slice(maker.as_ref());
     }
 
+    pub fn offer_id(&self) -> u64 {
+        u64::from_le_bytes(self.offer_id)
+    }
+
+    pub fn set_offer_id(&mut self, offer_id: u64) {
+        self.offer_id = offer_id.to_le_bytes();
+    }
+
     pub fn mint_a(&self) -> pinocchio::Address {
         pinocchio::Address::from(self.mint_a)
     }
@@ -92,37 +116,66 @@ impl Escrow  {
     pub fn set_amount_to_give(&mut self, amount: u64) {
         self.amount_to_give = amount.to_le_bytes();
     }
+
+    /// Unix timestamp after which the offer can no longer be taken, 0 if it never expires.
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    pub fn set_expiry(&mut self, expiry: i64) {
+        self.expiry = expiry.to_le_bytes();
+    }
+
+    /// The only signer allowed to take the offer, all zeroes if anyone may take it.
+    pub fn taker(&self) -> pinocchio::Address {
+        pinocchio::Address::from(self.taker)
+    }
+
+    pub fn set_taker(&mut self, taker: &pinocchio::Address) {
+        self.taker.copy_from_slice(taker.as_ref());
+    }
 }
 
 
 impl EscrowV2 {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 1;
+    pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 8 + 8 + 32 + 1;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         maker: [u8; 32],
+        offer_id: u64,
         mint_a: [u8; 32],
         mint_b: [u8; 32],
         amount_to_receive: u64,
         amount_to_give: u64,
+        expiry: i64,
+        taker: [u8; 32],
         bump: u8,
     ) -> Self {
         Self {
             maker,
+            offer_id: offer_id.to_le_bytes(),
             mint_a,
             mint_b,
             amount_to_receive: amount_to_receive.to_le_bytes(),
             amount_to_give: amount_to_give.to_le_bytes(),
+            expiry: expiry.to_le_bytes(),
+            taker,
             bump,
         }
     }
 
     pub fn from_account_info(account_info: &AccountView) -> Result<Self, ProgramError> {
-        let data = account_info.try_borrow()?;
+        Self::from_bytes(&account_info.try_borrow()?)
+    }
+
+    /// Decodes an escrow from raw account data, e.g. as fetched off-chain.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() != EscrowV2::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        wincode::deserialize(&data).map_err(|_| ProgramError::InvalidAccountData)
+        wincode::deserialize(data).map_err(|_| ProgramError::InvalidAccountData)
     }
 
     pub fn save_to_account(&self, account_info: &AccountView) -> Result<(), ProgramError> {
@@ -144,6 +197,14 @@ impl EscrowV2 {
         self.maker.copy_from_slice(maker.as_ref());
     }
 
+    pub fn offer_id(&self) -> u64 {
+        u64::from_le_bytes(self.offer_id)
+    }
+
+    pub fn set_offer_id(&mut self, offer_id: u64) {
+        self.offer_id = offer_id.to_le_bytes();
+    }
+
     pub fn mint_a(&self) -> pinocchio::Address {
         pinocchio::Address::from(self.mint_a)
     }
@@ -175,4 +236,46 @@ impl EscrowV2 {
     pub fn set_amount_to_give(&mut self, amount: u64) {
         self.amount_to_give = amount.to_le_bytes();
     }
+
+    /// Unix timestamp after which the offer can no longer be taken, 0 if it never expires.
+    pub fn expiry(&self) -> i64 {
+        i64::from_le_bytes(self.expiry)
+    }
+
+    /// The only signer allowed to take the offer, all zeroes if anyone may take it.
+    pub fn taker(&self) -> pinocchio::Address {
+        pinocchio::Address::from(self.taker)
+    }
+}
+
+/// Whether an offer with the given `expiry` has expired at `now`. An expiry of 0 never expires.
+pub fn is_expired(expiry: i64, now: i64) -> bool {
+    expiry != 0 && now >= expiry
+}
+
+/// Whether `taker` may take an offer restricted to `designated`. An all-zero
+/// designated taker leaves the offer open to anyone.
+pub fn is_allowed_taker(designated: &pinocchio::Address, taker: &pinocchio::Address) -> bool {
+    designated == &pinocchio::Address::default() || designated == taker
+}
+
+/// Closes a program-owned escrow state account: wipes its data so it can't be
+/// revived within the same transaction, moves all of its lamports to
+/// `destination` and hands it back to the system program.
+pub fn close_escrow_account(account: &AccountView, destination: &AccountView) -> ProgramResult {
+    {
+        let mut data = account.try_borrow_mut()?;
+        data.fill(0);
+    }
+
+    let lamports = account.lamports();
+    destination.set_lamports(
+        destination
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+    );
+    account.set_lamports(0);
+
+    account.close()
 }
@@ -1,22 +1,27 @@
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
-    use std::str::FromStr;
 
     use litesvm::LiteSVM;
-    use litesvm_token::{spl_token, CreateAssociatedTokenAccount, CreateMint, MintTo};
+    use litesvm_token::{get_spl_account, spl_token, CreateAssociatedTokenAccount, CreateMint, MintTo};
 
-    use solana_instruction::{AccountMeta, Instruction};
+    use solana_clock::Clock;
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_native_token::LAMPORTS_PER_SOL;
     use solana_pubkey::Pubkey;
     use solana_signer::Signer;
     use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
+
+    use crate::client;
+    use crate::errors::EscrowError;
+    use crate::instructions::MakeArgs;
 
     const PROGRAM_ID: &str = "4ibrEMW5F6hKnkW4jVedswYv6H6VtwPN6ar6dvXDN1nT";
-    const TOKEN_PROGRAM_ID: Pubkey = spl_token::ID;
-    const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+    const TOKEN_PROGRAM_ID: Pubkey = client::TOKEN_PROGRAM;
+    const TOKEN_2022_PROGRAM_ID: Pubkey = client::TOKEN_2022_PROGRAM;
 
     struct SetupData {
         svm: LiteSVM,
@@ -33,17 +38,179 @@ mod tests {
         maker_ata_a: Pubkey,
         amount_to_give: u64,
         amount_to_receive: u64,
-        bump: u8,
-        system_program: Pubkey,
-        token_program: Pubkey,
-        associated_token_program: Pubkey,
+        offer_id: u64,
+        expiry: i64,
+        designated_taker: Pubkey,
+        token_program_a: Pubkey,
+        token_program_b: Pubkey,
     }
 
     fn program_id() -> Pubkey {
-        Pubkey::from_str(PROGRAM_ID).unwrap()
+        client::PROGRAM_ID
+    }
+
+    fn make_builder(setup: &SetupData) -> client::Make {
+        client::Make {
+            maker: setup.maker.pubkey(),
+            mint_a: setup.mint_a,
+            mint_b: setup.mint_b,
+            token_program_a: setup.token_program_a,
+            offer_id: setup.offer_id,
+            amount_to_receive: setup.amount_to_receive,
+            amount_to_give: setup.amount_to_give,
+            expiry: setup.expiry,
+            taker: setup.designated_taker,
+        }
+    }
+
+    fn make_escrow(setup: &mut SetupData) {
+        let message = Message::new(&[make_builder(setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+    }
+
+    fn take_builder(setup: &SetupData) -> client::Take {
+        client::Take {
+            taker: setup.taker.pubkey(),
+            maker: setup.maker.pubkey(),
+            mint_a: setup.mint_a,
+            mint_b: setup.mint_b,
+            token_program_a: setup.token_program_a,
+            token_program_b: setup.token_program_b,
+            offer_id: setup.offer_id,
+            hook_accounts_a: 0,
+        }
+    }
+
+    fn cancel_builder(setup: &SetupData) -> client::Cancel {
+        client::Cancel {
+            maker: setup.maker.pubkey(),
+            mint_a: setup.mint_a,
+            token_program_a: setup.token_program_a,
+            offer_id: setup.offer_id,
+        }
+    }
+
+    fn expire_builder(setup: &SetupData) -> client::Expire {
+        client::Expire {
+            maker: setup.maker.pubkey(),
+            mint_a: setup.mint_a,
+            token_program_a: setup.token_program_a,
+            offer_id: setup.offer_id,
+        }
+    }
+
+    fn amend_builder(setup: &SetupData, amount_to_receive: u64, deposit: u64, withdraw: u64) -> client::Amend {
+        client::Amend {
+            maker: setup.maker.pubkey(),
+            mint_a: setup.mint_a,
+            token_program_a: setup.token_program_a,
+            offer_id: setup.offer_id,
+            amount_to_receive,
+            deposit,
+            withdraw,
+        }
+    }
+
+    fn escrow_state(setup: &SetupData) -> client::Escrow {
+        let escrow = setup.svm.get_account(&setup.escrow_account).unwrap();
+        client::decode_escrow(&escrow.data).unwrap()
+    }
+
+    fn warp_to_timestamp(svm: &mut LiteSVM, unix_timestamp: i64) {
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = unix_timestamp;
+        svm.set_sysvar(&clock);
+    }
+
+    fn assert_escrow_error(setup: &mut SetupData, ix: Instruction, signer: &Keypair, expected: EscrowError) {
+        let message = Message::new(&[ix], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, signer], message, recent_blockhash);
+        let err = setup.svm.send_transaction(transaction).unwrap_err();
+
+        assert_eq!(
+            err.err,
+            TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)),
+        );
+    }
+
+    fn escrow_amounts(setup: &SetupData) -> (u64, u64) {
+        let escrow = escrow_state(setup);
+        (escrow.amount_to_receive(), escrow.amount_to_give())
     }
 
     fn setup() -> SetupData {
+        setup_with(TOKEN_PROGRAM_ID, None)
+    }
+
+    /// Token balance read straight from account data, so it works for
+    /// Token-2022 accounts carrying extensions as well.
+    fn token_amount(svm: &LiteSVM, account: &Pubkey) -> u64 {
+        let account = svm.get_account(account).unwrap();
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    /// Creates a Token-2022 mint with a transfer fee of `fee_bps` and no fee cap.
+    fn create_transfer_fee_mint(svm: &mut LiteSVM, payer: &Keypair, decimals: u8, fee_bps: u16) -> Pubkey {
+        // Mint base layout, account type, and the TransferFeeConfig TLV entry.
+        const TRANSFER_FEE_MINT_LEN: u64 = 166 + 4 + 108;
+
+        let mint = Keypair::new();
+        let lamports = svm.minimum_balance_for_rent_exemption(TRANSFER_FEE_MINT_LEN as usize);
+
+        let create_account_data = [
+            0u32.to_le_bytes().to_vec(),
+            lamports.to_le_bytes().to_vec(),
+            TRANSFER_FEE_MINT_LEN.to_le_bytes().to_vec(),
+            TOKEN_2022_PROGRAM_ID.to_bytes().to_vec(),
+        ]
+        .concat();
+        let create_account_ix = Instruction {
+            program_id: solana_sdk_ids::system_program::ID,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(mint.pubkey(), true),
+            ],
+            data: create_account_data,
+        };
+
+        // InitializeTransferFeeConfig without config or withdraw authorities.
+        let fee_config_data = [
+            vec![26, 0, 0, 0],
+            fee_bps.to_le_bytes().to_vec(),
+            u64::MAX.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        let fee_config_ix = Instruction {
+            program_id: TOKEN_2022_PROGRAM_ID,
+            accounts: vec![AccountMeta::new(mint.pubkey(), false)],
+            data: fee_config_data,
+        };
+
+        // InitializeMint2 with the payer as mint authority and no freeze authority.
+        let init_mint_data = [vec![20, decimals], payer.pubkey().to_bytes().to_vec(), vec![0]].concat();
+        let init_mint_ix = Instruction {
+            program_id: TOKEN_2022_PROGRAM_ID,
+            accounts: vec![AccountMeta::new(mint.pubkey(), false)],
+            data: init_mint_data,
+        };
+
+        let message = Message::new(
+            &[create_account_ix, fee_config_ix, init_mint_ix],
+            Some(&payer.pubkey()),
+        );
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[payer, &mint], message, recent_blockhash);
+        svm.send_transaction(transaction).unwrap();
+
+        mint.pubkey()
+    }
+
+    /// Sets up an escrow whose mint A lives under `token_program_a`, optionally
+    /// with a Token-2022 transfer fee. Mint B is always a legacy Token mint.
+    fn setup_with(token_program_a: Pubkey, transfer_fee_bps: Option<u16>) -> SetupData {
         let mut svm = LiteSVM::new();
         let payer = Keypair::new();
         let taker = Keypair::new();
@@ -66,11 +233,18 @@ mod tests {
             .expect("Failed to add program");
 
         // Create mints
-        let mint_a = CreateMint::new(&mut svm, &payer)
-            .decimals(6)
-            .authority(&payer.pubkey())
-            .send()
-            .unwrap();
+        let mint_a = match transfer_fee_bps {
+            Some(fee_bps) => {
+                assert_eq!(token_program_a, TOKEN_2022_PROGRAM_ID);
+                create_transfer_fee_mint(&mut svm, &payer, 6, fee_bps)
+            }
+            None => CreateMint::new(&mut svm, &payer)
+                .decimals(6)
+                .authority(&payer.pubkey())
+                .token_program_id(&token_program_a)
+                .send()
+                .unwrap(),
+        };
         let mint_b = CreateMint::new(&mut svm, &payer)
             .decimals(6)
             .authority(&payer.pubkey())
@@ -80,6 +254,7 @@ mod tests {
         // Create associated token accounts
         let maker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &payer, &mint_a)
             .owner(&maker.pubkey())
+            .token_program_id(&token_program_a)
             .send()
             .unwrap();
         let maker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &payer, &mint_b)
@@ -88,6 +263,7 @@ mod tests {
             .unwrap();
         let taker_ata_a = CreateAssociatedTokenAccount::new(&mut svm, &payer, &mint_a)
             .owner(&taker.pubkey())
+            .token_program_id(&token_program_a)
             .send()
             .unwrap();
         let taker_ata_b = CreateAssociatedTokenAccount::new(&mut svm, &payer, &mint_b)
@@ -96,24 +272,17 @@ mod tests {
             .unwrap();
 
         // Derive escrow PDA
-        let (escrow_account, bump) = Pubkey::find_program_address(
-            &[b"escrow".as_ref(), maker.pubkey().as_ref()],
-            &program_id,
-        );
+        let offer_id: u64 = 1;
+        let (escrow_account, _) = client::find_escrow_address(&maker.pubkey(), offer_id);
 
         // Derive vault ATA
-        let escrow_vault = spl_associated_token_account::get_associated_token_address(
-            &escrow_account,
-            &mint_a,
-        );
+        let escrow_vault = client::find_vault_address(&escrow_account, &mint_a, &token_program_a);
 
-        // Define program IDs
-        let associated_token_program = ASSOCIATED_TOKEN_PROGRAM_ID.parse::<Pubkey>().unwrap();
-        let token_program = TOKEN_PROGRAM_ID;
-        let system_program = solana_sdk_ids::system_program::ID;
+        let token_program_b = TOKEN_PROGRAM_ID;
 
         // Mint tokens to maker's ATA A
         MintTo::new(&mut svm, &payer, &mint_a, &maker_ata_a, 1_000_000_000)
+            .token_program_id(&token_program_a)
             .send()
             .unwrap();
 
@@ -135,10 +304,11 @@ mod tests {
             maker_ata_a,
             amount_to_give,
             amount_to_receive,
-            bump,
-            system_program,
-            token_program,
-            associated_token_program,
+            offer_id,
+            expiry: 0,
+            designated_taker: Pubkey::default(),
+            token_program_a,
+            token_program_b,
         }
     }
 
@@ -150,29 +320,7 @@ mod tests {
         assert_eq!(program_id.to_string(), PROGRAM_ID);
 
         // Create the "Make" instruction
-        let make_data = [
-            vec![0u8], // Discriminator for "Make" instruction
-            setup.bump.to_le_bytes().to_vec(),
-            setup.amount_to_receive.to_le_bytes().to_vec(),
-            setup.amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
-
-        let make_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.maker.pubkey(), true),
-                AccountMeta::new(setup.mint_a, false),
-                AccountMeta::new(setup.mint_b, false),
-                AccountMeta::new(setup.escrow_account, false),
-                AccountMeta::new(setup.maker_ata_a, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new(setup.system_program, false),
-                AccountMeta::new(setup.token_program, false),
-                AccountMeta::new(setup.associated_token_program, false),
-            ],
-            data: make_data,
-        };
+        let make_ix = make_builder(&setup).instruction();
 
         let message = Message::new(&[make_ix], Some(&setup.payer.pubkey()));
         let recent_blockhash = setup.svm.latest_blockhash();
@@ -188,32 +336,8 @@ mod tests {
     fn test_make_v2_instruction() {
         let mut setup = setup();
 
-        let program_id = program_id();
-
-        // Create the "MakeV2" instruction (discriminator = 3)
-        let make_v2_data = [
-            vec![3u8], // Discriminator for "MakeV2" instruction
-            setup.bump.to_le_bytes().to_vec(),
-            setup.amount_to_receive.to_le_bytes().to_vec(),
-            setup.amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
-
-        let make_v2_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.maker.pubkey(), true),
-                AccountMeta::new(setup.mint_a, false),
-                AccountMeta::new(setup.mint_b, false),
-                AccountMeta::new(setup.escrow_account, false),
-                AccountMeta::new(setup.maker_ata_a, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new(setup.system_program, false),
-                AccountMeta::new(setup.token_program, false),
-                AccountMeta::new(setup.associated_token_program, false),
-            ],
-            data: make_v2_data,
-        };
+        // Create the "MakeV2" instruction
+        let make_v2_ix = make_builder(&setup).instruction_v2();
 
         let message = Message::new(&[make_v2_ix], Some(&setup.payer.pubkey()));
         let recent_blockhash = setup.svm.latest_blockhash();
@@ -221,6 +345,12 @@ mod tests {
 
         let tx = setup.svm.send_transaction(transaction).unwrap();
 
+        let escrow = setup.svm.get_account(&setup.escrow_account).unwrap();
+        let escrow = client::decode_escrow_v2(&escrow.data).unwrap();
+        assert_eq!(escrow.maker(), setup.maker.pubkey());
+        assert_eq!(escrow.offer_id(), setup.offer_id);
+        assert_eq!(escrow.amount_to_give(), setup.amount_to_give);
+
         println!("\nMakeV2 transaction successful");
         println!("CUs Consumed: {}", tx.compute_units_consumed);
     }
@@ -229,63 +359,16 @@ mod tests {
     fn test_take_instruction() {
         let mut setup = setup();
 
-        let program_id = program_id();
-
         // First, create the escrow using Make instruction
-        let make_data = [
-            vec![0u8],
-            setup.bump.to_le_bytes().to_vec(),
-            setup.amount_to_receive.to_le_bytes().to_vec(),
-            setup.amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
-
-        let make_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.maker.pubkey(), true),
-                AccountMeta::new(setup.mint_a, false),
-                AccountMeta::new(setup.mint_b, false),
-                AccountMeta::new(setup.escrow_account, false),
-                AccountMeta::new(setup.maker_ata_a, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new(setup.system_program, false),
-                AccountMeta::new(setup.token_program, false),
-                AccountMeta::new(setup.associated_token_program, false),
-            ],
-            data: make_data,
-        };
-
-        let message = Message::new(&[make_ix], Some(&setup.payer.pubkey()));
-        let recent_blockhash = setup.svm.latest_blockhash();
-        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
-        setup.svm.send_transaction(transaction).unwrap();
+        make_escrow(&mut setup);
 
         // Mint tokens to taker's ATA B (for the exchange)
         MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_give)
             .send()
             .unwrap();
 
-        // Now execute the Take instruction (discriminator = 1)
-        let take_data = vec![1u8]; // Discriminator for "Take" instruction
-
-        let take_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.taker.pubkey(), true),
-                AccountMeta::new(setup.maker.pubkey(), false),
-                AccountMeta::new(setup.mint_a, false),
-                AccountMeta::new(setup.mint_b, false),
-                AccountMeta::new(setup.escrow_account, false),
-                AccountMeta::new(setup.taker_ata_a, false),
-                AccountMeta::new(setup.taker_ata_b, false),
-                AccountMeta::new(setup.maker_ata_b, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new_readonly(setup.token_program, false),
-                AccountMeta::new(setup.associated_token_program, false),
-            ],
-            data: take_data,
-        };
+        // Now execute the Take instruction
+        let take_ix = take_builder(&setup).instruction();
 
         let message = Message::new(&[take_ix], Some(&setup.taker.pubkey()));
         let recent_blockhash = setup.svm.latest_blockhash();
@@ -301,62 +384,999 @@ mod tests {
     fn test_cancel_instruction() {
         let mut setup = setup();
 
-        let program_id = program_id();
-
         // First, create the escrow using Make instruction
-        let make_data = [
-            vec![0u8],
-            setup.bump.to_le_bytes().to_vec(),
-            setup.amount_to_receive.to_le_bytes().to_vec(),
-            setup.amount_to_give.to_le_bytes().to_vec(),
-        ]
-        .concat();
+        make_escrow(&mut setup);
 
-        let make_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.maker.pubkey(), true),
-                AccountMeta::new(setup.mint_a, false),
-                AccountMeta::new(setup.mint_b, false),
-                AccountMeta::new(setup.escrow_account, false),
-                AccountMeta::new(setup.maker_ata_a, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new(setup.system_program, false),
-                AccountMeta::new(setup.token_program, false),
-                AccountMeta::new(setup.associated_token_program, false),
-            ],
-            data: make_data,
-        };
+        // Now execute the Cancel instruction
+        let cancel_ix = cancel_builder(&setup).instruction();
 
-        let message = Message::new(&[make_ix], Some(&setup.payer.pubkey()));
+        let message = Message::new(&[cancel_ix], Some(&setup.maker.pubkey()));
         let recent_blockhash = setup.svm.latest_blockhash();
-        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
-        setup.svm.send_transaction(transaction).unwrap();
+        let transaction = Transaction::new(&[&setup.maker], message, recent_blockhash);
 
-        // Now execute the Cancel instruction (discriminator = 2)
-        let cancel_data = vec![2u8]; // Discriminator for "Cancel" instruction
+        let tx = setup.svm.send_transaction(transaction).unwrap();
 
-        let cancel_ix = Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(setup.maker.pubkey(), true),
-                AccountMeta::new_readonly(setup.escrow_account, false),
-                AccountMeta::new(setup.escrow_vault, false),
-                AccountMeta::new(setup.maker_ata_a, false),
-                AccountMeta::new_readonly(setup.system_program, false),
-                AccountMeta::new_readonly(setup.token_program, false),
-                AccountMeta::new_readonly(setup.associated_token_program, false),
-            ],
-            data: cancel_data,
-        };
+        println!("\nCancel transaction successful");
+        println!("CUs Consumed: {}", tx.compute_units_consumed);
+    }
+
+    #[test]
+    fn test_multiple_offers_per_maker() {
+        let mut setup = setup();
+
+        let offer_ids = [7u64, 8, 9];
+        setup.amount_to_give = 100_000_000;
+        setup.amount_to_receive = 50_000_000;
+
+        // Open several offers from the same maker, each with its own escrow PDA and vault
+        let mut offers = Vec::new();
+        for offer_id in offer_ids {
+            let (escrow_account, _) = client::find_escrow_address(&setup.maker.pubkey(), offer_id);
+            let escrow_vault = client::find_vault_address(&escrow_account, &setup.mint_a, &setup.token_program_a);
+
+            let make_ix = client::Make { offer_id, ..make_builder(&setup) }.instruction();
+
+            let message = Message::new(&[make_ix], Some(&setup.payer.pubkey()));
+            let recent_blockhash = setup.svm.latest_blockhash();
+            let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+            setup.svm.send_transaction(transaction).unwrap();
+
+            offers.push((escrow_account, escrow_vault));
+        }
+
+        // Every offer is live and records its own id
+        for (offer_id, (escrow_account, escrow_vault)) in offer_ids.iter().zip(offers.iter()) {
+            let escrow = setup.svm.get_account(escrow_account).unwrap();
+            assert_eq!(client::decode_escrow(&escrow.data).unwrap().offer_id(), *offer_id);
+
+            let vault: spl_token::state::Account = get_spl_account(&setup.svm, escrow_vault).unwrap();
+            assert_eq!(vault.amount, setup.amount_to_give);
+        }
+
+        // Take the second offer
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_give)
+            .send()
+            .unwrap();
+
+        let take_ix = client::Take { offer_id: offer_ids[1], ..take_builder(&setup) }.instruction();
+
+        let message = Message::new(&[take_ix], Some(&setup.taker.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        // Cancel the third offer
+        let cancel_ix = client::Cancel { offer_id: offer_ids[2], ..cancel_builder(&setup) }.instruction();
 
         let message = Message::new(&[cancel_ix], Some(&setup.maker.pubkey()));
         let recent_blockhash = setup.svm.latest_blockhash();
         let transaction = Transaction::new(&[&setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
 
-        let tx = setup.svm.send_transaction(transaction).unwrap();
+        // The first offer is untouched
+        let (open_escrow, open_vault) = offers[0];
+        assert!(setup.svm.get_account(&open_escrow).is_some());
+        let vault: spl_token::state::Account = get_spl_account(&setup.svm, &open_vault).unwrap();
+        assert_eq!(vault.amount, setup.amount_to_give);
+    }
 
-        println!("\nCancel transaction successful");
-        println!("CUs Consumed: {}", tx.compute_units_consumed);
+    #[test]
+    fn test_take_closes_escrow() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let escrow_rent = setup.svm.get_account(&setup.escrow_account).unwrap().lamports;
+        let vault_rent = setup.svm.get_account(&setup.escrow_vault).unwrap().lamports;
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.taker.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        // Both the escrow state and the vault are gone and their rent went to the maker
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        let maker_lamports_after = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        assert_eq!(maker_lamports_after, maker_lamports + escrow_rent + vault_rent);
+
+        let taker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+        assert_eq!(taker_ata_a.amount, setup.amount_to_give);
+        let maker_ata_b: spl_token::state::Account = get_spl_account(&setup.svm, &setup.maker_ata_b).unwrap();
+        assert_eq!(maker_ata_b.amount, setup.amount_to_receive);
+
+        // The same escrow can't be taken twice
+        setup.svm.expire_blockhash();
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.taker.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+        assert!(setup.svm.send_transaction(transaction).is_err());
+    }
+
+    #[test]
+    fn test_cancel_closes_escrow() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        let escrow_rent = setup.svm.get_account(&setup.escrow_account).unwrap().lamports;
+        let vault_rent = setup.svm.get_account(&setup.escrow_vault).unwrap().lamports;
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+
+        // The payer covers the fee so the maker balance only reflects the reclaimed rent
+        let message = Message::new(&[cancel_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        let maker_lamports_after = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        assert_eq!(maker_lamports_after, maker_lamports + escrow_rent + vault_rent);
+
+        let maker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.maker_ata_a).unwrap();
+        assert_eq!(maker_ata_a.amount, 1_000_000_000);
+
+        // A cancelled escrow can't be cancelled again
+        setup.svm.expire_blockhash();
+        let message = Message::new(&[cancel_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        assert!(setup.svm.send_transaction(transaction).is_err());
+    }
+
+    #[test]
+    fn test_v2_lifecycle_compute_units() {
+        // Zero-copy V1 path: Make -> Take
+        let mut v1 = setup();
+        MintTo::new(&mut v1.svm, &v1.payer, &v1.mint_b, &v1.taker_ata_b, v1.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[make_builder(&v1).instruction()], Some(&v1.payer.pubkey()));
+        let transaction = Transaction::new(&[&v1.payer, &v1.maker], message, v1.svm.latest_blockhash());
+        let v1_make = v1.svm.send_transaction(transaction).unwrap().compute_units_consumed;
+
+        let message = Message::new(&[take_builder(&v1).instruction()], Some(&v1.taker.pubkey()));
+        let transaction = Transaction::new(&[&v1.taker], message, v1.svm.latest_blockhash());
+        let v1_take = v1.svm.send_transaction(transaction).unwrap().compute_units_consumed;
+
+        // Wincode V2 path: MakeV2 -> TakeV2
+        let mut v2 = setup();
+        MintTo::new(&mut v2.svm, &v2.payer, &v2.mint_b, &v2.taker_ata_b, v2.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[make_builder(&v2).instruction_v2()], Some(&v2.payer.pubkey()));
+        let transaction = Transaction::new(&[&v2.payer, &v2.maker], message, v2.svm.latest_blockhash());
+        let v2_make = v2.svm.send_transaction(transaction).unwrap().compute_units_consumed;
+
+        let message = Message::new(&[take_builder(&v2).instruction_v2()], Some(&v2.taker.pubkey()));
+        let transaction = Transaction::new(&[&v2.taker], message, v2.svm.latest_blockhash());
+        let v2_take = v2.svm.send_transaction(transaction).unwrap().compute_units_consumed;
+
+        assert!(v2.svm.get_account(&v2.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert!(v2.svm.get_account(&v2.escrow_vault).is_none_or(|a| a.lamports == 0));
+        let taker_ata_a: spl_token::state::Account = get_spl_account(&v2.svm, &v2.taker_ata_a).unwrap();
+        assert_eq!(taker_ata_a.amount, v2.amount_to_give);
+
+        // Wincode V2 path: MakeV2 -> CancelV2
+        let mut v2_cancel = setup();
+        let message = Message::new(&[make_builder(&v2_cancel).instruction_v2()], Some(&v2_cancel.payer.pubkey()));
+        let transaction = Transaction::new(&[&v2_cancel.payer, &v2_cancel.maker], message, v2_cancel.svm.latest_blockhash());
+        v2_cancel.svm.send_transaction(transaction).unwrap();
+
+        let message = Message::new(&[cancel_builder(&v2_cancel).instruction_v2()], Some(&v2_cancel.maker.pubkey()));
+        let transaction = Transaction::new(&[&v2_cancel.maker], message, v2_cancel.svm.latest_blockhash());
+        let v2_cancel_cu = v2_cancel.svm.send_transaction(transaction).unwrap().compute_units_consumed;
+
+        assert!(v2_cancel.svm.get_account(&v2_cancel.escrow_account).is_none_or(|a| a.lamports == 0));
+        let maker_ata_a: spl_token::state::Account = get_spl_account(&v2_cancel.svm, &v2_cancel.maker_ata_a).unwrap();
+        assert_eq!(maker_ata_a.amount, 1_000_000_000);
+
+        println!("\nCompute units, V1 (zero-copy) vs V2 (wincode)");
+        println!("Make:  {} vs {}", v1_make, v2_make);
+        println!("Take:  {} vs {}", v1_take, v2_take);
+        println!("Total: {} vs {}", v1_make + v1_take, v2_make + v2_take);
+        println!("CancelV2: {}", v2_cancel_cu);
+
+        // Wincode may cost a little over zero-copy, but not more than this per instruction
+        const MAX_WINCODE_OVERHEAD: u64 = 2_000;
+        assert!(v2_make <= v1_make + MAX_WINCODE_OVERHEAD, "MakeV2 used {} CUs, Make {}", v2_make, v1_make);
+        assert!(v2_take <= v1_take + MAX_WINCODE_OVERHEAD, "TakeV2 used {} CUs, Take {}", v2_take, v1_take);
+        // Cancelling moves one mint where a take moves two
+        assert!(v2_cancel_cu <= v2_take, "CancelV2 used {} CUs, TakeV2 {}", v2_cancel_cu, v2_take);
+    }
+
+    #[test]
+    fn test_take_partial_multiple_fills() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        // 500 A for 100 B, filled as 25 + 25 + 50
+        let fills = [25_000_000u64, 25_000_000, 50_000_000];
+        let mut paid_b = 0;
+        for (i, amount_b) in fills.into_iter().enumerate() {
+            let message = Message::new(&[take_builder(&setup).partial_instruction(amount_b)], Some(&setup.taker.pubkey()));
+            let recent_blockhash = setup.svm.latest_blockhash();
+            let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+            let tx = setup.svm.send_transaction(transaction).unwrap();
+            println!("TakePartial #{} CUs Consumed: {}", i + 1, tx.compute_units_consumed);
+
+            paid_b += amount_b;
+            let taker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+            assert_eq!(taker_ata_a.amount, paid_b * 5);
+            let maker_ata_b: spl_token::state::Account = get_spl_account(&setup.svm, &setup.maker_ata_b).unwrap();
+            assert_eq!(maker_ata_b.amount, paid_b);
+
+            if paid_b < setup.amount_to_receive {
+                let (amount_to_receive, amount_to_give) = escrow_amounts(&setup);
+                assert_eq!(amount_to_receive, setup.amount_to_receive - paid_b);
+                assert_eq!(amount_to_give, setup.amount_to_give - paid_b * 5);
+            }
+        }
+
+        // Fully filled, so the escrow closed itself
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+    }
+
+    #[test]
+    fn test_take_partial_rounding_remainder() {
+        let mut setup = setup();
+        setup.amount_to_give = 1_000;
+        setup.amount_to_receive = 3;
+        make_escrow(&mut setup);
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, 10)
+            .send()
+            .unwrap();
+
+        // Paying more than the escrow still wants is rejected
+        let message = Message::new(&[take_builder(&setup).partial_instruction(4)], Some(&setup.taker.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+        assert!(setup.svm.send_transaction(transaction).is_err());
+
+        // 1000 / 3 rounds down on every fill, the last fill sweeps the remainder
+        for expected in [333u64, 333, 334] {
+            let before: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+
+            setup.svm.expire_blockhash();
+            let message = Message::new(&[take_builder(&setup).partial_instruction(1)], Some(&setup.taker.pubkey()));
+            let recent_blockhash = setup.svm.latest_blockhash();
+            let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+            setup.svm.send_transaction(transaction).unwrap();
+
+            let after: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+            assert_eq!(after.amount - before.amount, expected);
+        }
+
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        let maker_ata_b: spl_token::state::Account = get_spl_account(&setup.svm, &setup.maker_ata_b).unwrap();
+        assert_eq!(maker_ata_b.amount, 3);
+    }
+
+    #[test]
+    fn test_token_2022_make_and_take() {
+        let mut setup = setup_with(TOKEN_2022_PROGRAM_ID, None);
+        make_escrow(&mut setup);
+
+        assert_eq!(token_amount(&setup.svm, &setup.escrow_vault), setup.amount_to_give);
+        assert_eq!(escrow_amounts(&setup), (setup.amount_to_receive, setup.amount_to_give));
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_amount(&setup.svm, &setup.taker_ata_a), setup.amount_to_give);
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_b), setup.amount_to_receive);
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+    }
+
+    #[test]
+    fn test_token_2022_transfer_fee_records_net_amount() {
+        // 1% fee on every transfer of mint A
+        let mut setup = setup_with(TOKEN_2022_PROGRAM_ID, Some(100));
+        make_escrow(&mut setup);
+
+        let net_deposit = setup.amount_to_give - setup.amount_to_give / 100;
+        assert_eq!(token_amount(&setup.svm, &setup.escrow_vault), net_deposit);
+        assert_eq!(escrow_amounts(&setup), (setup.amount_to_receive, net_deposit));
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        // The vault pays the fee again on the way out; withheld fees are
+        // harvested to the mint so the vault can still be closed.
+        assert_eq!(token_amount(&setup.svm, &setup.taker_ata_a), net_deposit - net_deposit / 100);
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_b), setup.amount_to_receive);
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+    }
+
+    #[test]
+    fn test_token_2022_transfer_fee_cancel() {
+        let mut setup = setup_with(TOKEN_2022_PROGRAM_ID, Some(100));
+        make_escrow(&mut setup);
+
+        let net_deposit = setup.amount_to_give - setup.amount_to_give / 100;
+        let maker_balance = token_amount(&setup.svm, &setup.maker_ata_a);
+
+        let message = Message::new(&[cancel_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(
+            token_amount(&setup.svm, &setup.maker_ata_a),
+            maker_balance + net_deposit - net_deposit / 100
+        );
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+    }
+
+    #[test]
+    fn test_make_rejects_pda_mismatch() {
+        let mut setup = setup();
+
+        // The offer id in the data no longer matches the escrow account's seeds
+        let mut ix = make_builder(&setup).instruction();
+        ix.data[18..26].copy_from_slice(&(setup.offer_id + 1).to_le_bytes());
+
+        let maker = setup.maker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &maker, EscrowError::PdaMismatch);
+    }
+
+    #[test]
+    fn test_make_rejects_already_initialized() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+        setup.svm.expire_blockhash();
+
+        let ix = make_builder(&setup).instruction();
+        let maker = setup.maker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &maker, EscrowError::AlreadyInitialized);
+    }
+
+    #[test]
+    fn test_take_rejects_missing_signature() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        let mut ix = take_builder(&setup).instruction();
+        ix.accounts[0].is_signer = false;
+
+        // Only the fee payer signs
+        let payer = setup.payer.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &payer, EscrowError::MissingRequiredSignature);
+    }
+
+    #[test]
+    fn test_take_rejects_wrong_mint() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        // Claim the maker wants mint A back instead of mint B
+        let mut ix = take_builder(&setup).instruction();
+        ix.accounts[3].pubkey = setup.mint_a;
+
+        let taker = setup.taker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::MintMismatch);
+    }
+
+    #[test]
+    fn test_take_rejects_hook_accounts_past_the_end() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        // Claim mint A has a hook account without passing any
+        let ix = client::Take { hook_accounts_a: 1, ..take_builder(&setup) }.instruction();
+
+        let message = Message::new(&[ix], Some(&setup.taker.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.taker], message, recent_blockhash);
+        let err = setup.svm.send_transaction(transaction).unwrap_err();
+
+        assert_eq!(err.err, TransactionError::InstructionError(0, InstructionError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn test_cancel_rejects_wrong_owner() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        // The taker tries to cancel the maker's offer into their own account
+        let mut ix = cancel_builder(&setup).instruction();
+        ix.accounts[0].pubkey = setup.taker.pubkey();
+        ix.accounts[4].pubkey = setup.taker_ata_a;
+
+        let taker = setup.taker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::InvalidOwner);
+    }
+
+    #[test]
+    fn test_take_before_and_after_expiry() {
+        let mut setup = setup();
+        let now = setup.svm.get_sysvar::<Clock>().unix_timestamp;
+        setup.expiry = now + 3_600;
+        make_escrow(&mut setup);
+
+        assert_eq!(escrow_state(&setup).expiry(), setup.expiry);
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        // At the deadline the offer is already gone
+        warp_to_timestamp(&mut setup.svm, setup.expiry);
+        let ix = take_builder(&setup).instruction();
+        let taker = setup.taker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::OfferExpired);
+
+        // One second earlier it can still be taken
+        warp_to_timestamp(&mut setup.svm, setup.expiry - 1);
+        setup.svm.expire_blockhash();
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        let taker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+        assert_eq!(taker_ata_a.amount, setup.amount_to_give);
+    }
+
+    #[test]
+    fn test_expire_refunds_maker() {
+        let mut setup = setup();
+        let now = setup.svm.get_sysvar::<Clock>().unix_timestamp;
+        setup.expiry = now + 3_600;
+        make_escrow(&mut setup);
+
+        let maker_lamports = setup.svm.get_balance(&setup.maker.pubkey()).unwrap();
+        let rent = setup.svm.get_balance(&setup.escrow_account).unwrap()
+            + setup.svm.get_balance(&setup.escrow_vault).unwrap();
+
+        // Anyone may call it, but not before the deadline
+        warp_to_timestamp(&mut setup.svm, setup.expiry - 1);
+        let ix = expire_builder(&setup).instruction();
+        let payer = setup.payer.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &payer, EscrowError::OfferNotExpired);
+
+        warp_to_timestamp(&mut setup.svm, setup.expiry);
+        setup.svm.expire_blockhash();
+        let message = Message::new(&[expire_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        let maker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.maker_ata_a).unwrap();
+        assert_eq!(maker_ata_a.amount, 1_000_000_000);
+        assert_eq!(setup.svm.get_balance(&setup.maker.pubkey()).unwrap(), maker_lamports + rent);
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+    }
+
+    #[test]
+    fn test_expire_rejects_offer_without_expiry() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        warp_to_timestamp(&mut setup.svm, i64::MAX);
+        let ix = expire_builder(&setup).instruction();
+        let payer = setup.payer.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &payer, EscrowError::OfferNotExpired);
+    }
+
+    #[test]
+    fn test_take_open_to_anyone() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        assert_eq!(escrow_state(&setup).taker(), Pubkey::default());
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        let taker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+        assert_eq!(taker_ata_a.amount, setup.amount_to_give);
+    }
+
+    #[test]
+    fn test_take_restricted_to_designated_taker() {
+        let mut setup = setup();
+        setup.designated_taker = setup.taker.pubkey();
+        make_escrow(&mut setup);
+
+        assert_eq!(escrow_state(&setup).taker(), setup.taker.pubkey());
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        let taker_ata_a: spl_token::state::Account = get_spl_account(&setup.svm, &setup.taker_ata_a).unwrap();
+        assert_eq!(taker_ata_a.amount, setup.amount_to_give);
+    }
+
+    #[test]
+    fn test_take_rejects_other_taker() {
+        let mut setup = setup();
+        setup.designated_taker = Pubkey::new_unique();
+        make_escrow(&mut setup);
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let ix = take_builder(&setup).instruction();
+        let taker = setup.taker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::UnauthorizedTaker);
+
+        // Partial fills are held to the same restriction
+        let ix = take_builder(&setup).partial_instruction(setup.amount_to_receive / 2);
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::UnauthorizedTaker);
+    }
+
+    #[test]
+    fn test_amend_price_and_deposit() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        let escrow_lamports = setup.svm.get_balance(&setup.escrow_account).unwrap();
+
+        // Ask for more B and top up the deposit by 100 A
+        let amend_ix = amend_builder(&setup, 150_000_000, 100_000_000, 0).instruction();
+        let message = Message::new(&[amend_ix], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(escrow_amounts(&setup), (150_000_000, 600_000_000));
+        assert_eq!(token_amount(&setup.svm, &setup.escrow_vault), 600_000_000);
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_a), 400_000_000);
+        // Updated in place, no new rent
+        assert_eq!(setup.svm.get_balance(&setup.escrow_account).unwrap(), escrow_lamports);
+
+        // The taker pays and receives the amended amounts
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, 150_000_000)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(token_amount(&setup.svm, &setup.taker_ata_a), 600_000_000);
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_b), 150_000_000);
+    }
+
+    #[test]
+    fn test_amend_withdraw() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        let amend_ix = amend_builder(&setup, setup.amount_to_receive, 0, 200_000_000).instruction();
+        let message = Message::new(&[amend_ix], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(escrow_amounts(&setup), (setup.amount_to_receive, 300_000_000));
+        assert_eq!(token_amount(&setup.svm, &setup.escrow_vault), 300_000_000);
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_a), 700_000_000);
+
+        // Emptying the vault is a Cancel, not an Amend
+        let amend_ix = amend_builder(&setup, setup.amount_to_receive, 0, 300_000_000).instruction();
+        let message = Message::new(&[amend_ix], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        let err = setup.svm.send_transaction(transaction).unwrap_err();
+        assert_eq!(
+            err.err,
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+        );
+    }
+
+    #[test]
+    fn test_amend_rejects_wrong_owner() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        // The taker tries to reprice the maker's offer
+        let mut ix = amend_builder(&setup, 1, 0, 0).instruction();
+        ix.accounts[0].pubkey = setup.taker.pubkey();
+        ix.accounts[4].pubkey = setup.taker_ata_a;
+
+        let taker = setup.taker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &taker, EscrowError::InvalidOwner);
+    }
+
+    #[test]
+    fn test_amend_rejects_other_escrow_token_account() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        // A second account for mint A owned by the escrow PDA, off the ATA address
+        let decoy = Keypair::new();
+        let mut account = setup.svm.get_account(&setup.escrow_vault).unwrap();
+        account.data[64..72].copy_from_slice(&0u64.to_le_bytes());
+        setup.svm.set_account(decoy.pubkey(), account).unwrap();
+        let decoy_state: spl_token::state::Account = get_spl_account(&setup.svm, &decoy.pubkey()).unwrap();
+        assert_eq!(decoy_state.owner, setup.escrow_account);
+        assert_eq!(decoy_state.amount, 0);
+
+        let mut ix = amend_builder(&setup, setup.amount_to_receive, 0, 0).instruction();
+        ix.accounts[3].pubkey = decoy.pubkey();
+
+        let maker = setup.maker.insecure_clone();
+        assert_escrow_error(&mut setup, ix, &maker, EscrowError::PdaMismatch);
+        assert_eq!(escrow_state(&setup).amount_to_give(), setup.amount_to_give);
+    }
+
+    #[test]
+    fn test_native_sol_for_token() {
+        let mut setup = setup();
+        setup.mint_a = client::NATIVE_MINT;
+        setup.amount_to_give = 2 * LAMPORTS_PER_SOL;
+
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        make_escrow(&mut setup);
+
+        // The SOL sits in the escrow PDA on top of its rent, no vault is created
+        let escrow_rent = setup.svm.minimum_balance_for_rent_exemption(client::Escrow::LEN);
+        let escrow = setup.svm.get_account(&setup.escrow_account).unwrap();
+        assert_eq!(escrow.lamports, escrow_rent + setup.amount_to_give);
+        assert_eq!(
+            setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports,
+            maker_lamports - escrow.lamports,
+        );
+        assert_eq!(escrow_state(&setup).mint_a(), client::NATIVE_MINT);
+        assert_eq!(escrow_state(&setup).amount_to_give(), setup.amount_to_give);
+        let token_vault = client::find_associated_token_address(&setup.escrow_account, &client::NATIVE_MINT, &TOKEN_PROGRAM_ID);
+        assert!(setup.svm.get_account(&token_vault).is_none());
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        let taker_lamports = setup.svm.get_account(&setup.taker.pubkey()).unwrap().lamports;
+
+        // The payer covers the fee so the balances only reflect the trade
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert_eq!(
+            setup.svm.get_account(&setup.taker.pubkey()).unwrap().lamports,
+            taker_lamports + setup.amount_to_give,
+        );
+        assert_eq!(
+            setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports,
+            maker_lamports + escrow_rent,
+        );
+        assert_eq!(token_amount(&setup.svm, &setup.maker_ata_b), setup.amount_to_receive);
+        assert_eq!(token_amount(&setup.svm, &setup.taker_ata_b), 0);
+    }
+
+    #[test]
+    fn test_token_for_native_sol() {
+        let mut setup = setup();
+        setup.mint_b = client::NATIVE_MINT;
+        setup.amount_to_receive = LAMPORTS_PER_SOL;
+
+        make_escrow(&mut setup);
+        assert_eq!(escrow_state(&setup).mint_b(), client::NATIVE_MINT);
+
+        let escrow_rent = setup.svm.get_account(&setup.escrow_account).unwrap().lamports;
+        let vault_rent = setup.svm.get_account(&setup.escrow_vault).unwrap().lamports;
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        let taker_lamports = setup.svm.get_account(&setup.taker.pubkey()).unwrap().lamports;
+
+        // The payer covers the fee so the balances only reflect the trade
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert!(setup.svm.get_account(&setup.escrow_vault).is_none_or(|a| a.lamports == 0));
+        assert_eq!(token_amount(&setup.svm, &setup.taker_ata_a), setup.amount_to_give);
+        assert_eq!(
+            setup.svm.get_account(&setup.taker.pubkey()).unwrap().lamports,
+            taker_lamports - setup.amount_to_receive,
+        );
+        assert_eq!(
+            setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports,
+            maker_lamports + setup.amount_to_receive + escrow_rent + vault_rent,
+        );
+    }
+
+    #[test]
+    fn test_cancel_refunds_native_sol() {
+        let mut setup = setup();
+        setup.mint_a = client::NATIVE_MINT;
+        setup.amount_to_give = 2 * LAMPORTS_PER_SOL;
+
+        let maker_lamports = setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports;
+        make_escrow(&mut setup);
+
+        let message = Message::new(&[cancel_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        setup.svm.send_transaction(transaction).unwrap();
+
+        // Deposit and rent both come back, the payer covered the fees
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none_or(|a| a.lamports == 0));
+        assert_eq!(setup.svm.get_account(&setup.maker.pubkey()).unwrap().lamports, maker_lamports);
+    }
+
+    #[test]
+    fn test_native_mint_rejects_token_program() {
+        let mut setup = setup();
+        setup.mint_a = client::NATIVE_MINT;
+
+        // A native mint must be paired with the system program
+        let mut make_ix = make_builder(&setup).instruction();
+        make_ix.accounts[7] = AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false);
+
+        let message = Message::new(&[make_ix], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        let err = setup.svm.send_transaction(transaction).unwrap_err();
+
+        assert_eq!(
+            err.err,
+            TransactionError::InstructionError(0, InstructionError::IncorrectProgramId),
+        );
+    }
+
+    #[test]
+    fn test_amend_args_parsing() {
+        use crate::instructions::AmendArgs;
+
+        let data = [&7u64.to_le_bytes()[..], &9u64.to_le_bytes(), &0u64.to_le_bytes(), &[254]].concat();
+        assert_eq!(
+            AmendArgs::try_from(data.as_slice()).unwrap(),
+            AmendArgs { amount_to_receive: 7, deposit: 9, withdraw: 0, vault_bump: 254 }
+        );
+
+        // Depositing and withdrawing at once, or a zero price, is rejected
+        let both = [&7u64.to_le_bytes()[..], &9u64.to_le_bytes(), &1u64.to_le_bytes(), &[254]].concat();
+        assert!(AmendArgs::try_from(both.as_slice()).is_err());
+        let free = [&0u64.to_le_bytes()[..], &9u64.to_le_bytes(), &0u64.to_le_bytes(), &[254]].concat();
+        assert!(AmendArgs::try_from(free.as_slice()).is_err());
+        assert!(AmendArgs::try_from(&data[..AmendArgs::LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn test_take_args_parsing() {
+        use crate::instructions::{TakeArgs, TakePartialArgs};
+
+        assert_eq!(TakeArgs::try_from(&[2u8][..]).unwrap(), TakeArgs { hook_accounts_a: 2 });
+        assert!(TakeArgs::try_from(&[][..]).is_err());
+        assert!(TakeArgs::try_from(&[2u8, 0][..]).is_err());
+
+        let data = [&7u64.to_le_bytes()[..], &[3]].concat();
+        assert_eq!(
+            TakePartialArgs::try_from(data.as_slice()).unwrap(),
+            TakePartialArgs { amount_b: 7, hook_accounts_a: 3 }
+        );
+        assert!(TakePartialArgs::try_from(&data[..TakePartialArgs::LEN - 1]).is_err());
+
+        // The client puts the count after the other arguments
+        let setup = setup();
+        let take = client::Take { hook_accounts_a: 2, ..take_builder(&setup) };
+        assert_eq!(&take.instruction().data[1..], &[2]);
+        assert_eq!(&take.partial_instruction(7).data[1..], data[..8].iter().chain(&[2]).copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_events_make_take() {
+        let mut setup = setup();
+
+        let message = Message::new(&[make_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        let meta = setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(
+            client::parse_events(&meta.logs),
+            vec![client::EscrowEvent {
+                kind: client::EventKind::Make,
+                escrow: setup.escrow_account,
+                maker: setup.maker.pubkey(),
+                taker: Pubkey::default(),
+                mint_a: setup.mint_a,
+                mint_b: setup.mint_b,
+                amount_a: setup.amount_to_give,
+                amount_b: setup.amount_to_receive,
+            }]
+        );
+
+        MintTo::new(&mut setup.svm, &setup.payer, &setup.mint_b, &setup.taker_ata_b, setup.amount_to_receive)
+            .send()
+            .unwrap();
+
+        let message = Message::new(&[take_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.taker], message, recent_blockhash);
+        let meta = setup.svm.send_transaction(transaction).unwrap();
+
+        assert_eq!(
+            client::parse_events(&meta.logs),
+            vec![client::EscrowEvent {
+                kind: client::EventKind::Take,
+                escrow: setup.escrow_account,
+                maker: setup.maker.pubkey(),
+                taker: setup.taker.pubkey(),
+                mint_a: setup.mint_a,
+                mint_b: setup.mint_b,
+                amount_a: setup.amount_to_give,
+                amount_b: setup.amount_to_receive,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_events_cancel() {
+        let mut setup = setup();
+        make_escrow(&mut setup);
+
+        let message = Message::new(&[cancel_builder(&setup).instruction()], Some(&setup.payer.pubkey()));
+        let recent_blockhash = setup.svm.latest_blockhash();
+        let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+        let meta = setup.svm.send_transaction(transaction).unwrap();
+
+        let events = client::parse_events(&meta.logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, client::EventKind::Cancel);
+        assert_eq!(events[0].escrow, setup.escrow_account);
+        assert_eq!(events[0].mint_b, setup.mint_b);
+        assert_eq!(events[0].amount_a, setup.amount_to_give);
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = client::EscrowEvent {
+            kind: client::EventKind::TakePartial,
+            escrow: Pubkey::new_unique(),
+            maker: Pubkey::new_unique(),
+            taker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            amount_a: 333,
+            amount_b: u64::MAX,
+        };
+
+        let bytes = event.to_bytes();
+        assert_eq!(bytes[0], crate::events::EVENT_VERSION);
+        assert_eq!(client::EscrowEvent::from_bytes(&bytes).unwrap(), event);
+
+        // Unknown versions and truncated payloads are rejected
+        let mut future = bytes;
+        future[0] += 1;
+        assert!(client::EscrowEvent::from_bytes(&future).is_err());
+        assert!(client::EscrowEvent::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        use base64::Engine;
+        let logs = vec![
+            "Program log: unrelated".to_string(),
+            format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(bytes)),
+        ];
+        assert_eq!(client::parse_events(&logs), vec![event]);
+    }
+
+    #[test]
+    fn test_fill_amount_favours_maker() {
+        use crate::instructions::fill_amount;
+
+        assert_eq!(fill_amount(1, 3, 1_000).unwrap(), 333);
+        assert_eq!(fill_amount(2, 3, 1_000).unwrap(), 666);
+        assert_eq!(fill_amount(3, 3, 1_000).unwrap(), 1_000);
+        assert_eq!(fill_amount(u64::MAX - 1, u64::MAX, u64::MAX).unwrap(), u64::MAX - 1);
+        assert!(fill_amount(0, 3, 1_000).is_err());
+        assert!(fill_amount(4, 3, 1_000).is_err());
+        // Would release nothing for a non-zero payment
+        assert!(fill_amount(1, 1_000, 3).is_err());
+    }
+
+    #[test]
+    fn test_make_args_parsing() {
+        let data = [
+            vec![254u8],
+            7u64.to_le_bytes().to_vec(),
+            9u64.to_le_bytes().to_vec(),
+            u64::MAX.to_le_bytes().to_vec(),
+            (-1i64).to_le_bytes().to_vec(),
+            [3u8; 32].to_vec(),
+        ]
+        .concat();
+
+        let args = MakeArgs::try_from(data.as_slice()).unwrap();
+        assert_eq!(
+            args,
+            MakeArgs {
+                bump: 254,
+                amount_to_receive: 7,
+                amount_to_give: 9,
+                offer_id: u64::MAX,
+                expiry: -1,
+                taker: [3u8; 32],
+            }
+        );
+
+        assert!(MakeArgs::try_from(&data[..MakeArgs::LEN - 1]).is_err());
+        assert!(MakeArgs::try_from([data.clone(), vec![0]].concat().as_slice()).is_err());
+        assert!(MakeArgs::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn test_make_rejects_malformed_instruction_data() {
+        let mut setup = setup();
+        let valid = make_builder(&setup).instruction().data;
+
+        // Every truncation of the payload, a few extra bytes and one far oversized payload
+        let mut lengths: Vec<usize> = (0..MakeArgs::LEN).collect();
+        lengths.extend([MakeArgs::LEN + 1, MakeArgs::LEN + 8, 512]);
+
+        for discriminator in [0u8, 3u8] {
+            for &len in &lengths {
+                let mut data = vec![discriminator];
+                data.extend((0..len).map(|i| valid.get(i + 1).copied().unwrap_or(0xAB)));
+
+                let mut ix = make_builder(&setup).instruction();
+                ix.data = data;
+
+                let message = Message::new(&[ix], Some(&setup.payer.pubkey()));
+                let recent_blockhash = setup.svm.latest_blockhash();
+                let transaction = Transaction::new(&[&setup.payer, &setup.maker], message, recent_blockhash);
+                let err = setup.svm.send_transaction(transaction).unwrap_err();
+
+                assert_eq!(
+                    err.err,
+                    TransactionError::InstructionError(0, InstructionError::InvalidInstructionData),
+                    "discriminator {} with {} bytes of args",
+                    discriminator,
+                    len,
+                );
+            }
+        }
+
+        // Nothing was created along the way
+        assert!(setup.svm.get_account(&setup.escrow_account).is_none());
     }
 }
@@ -1,5 +1,11 @@
+use pinocchio::{Address, address::address};
+
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
 pub const MIN_AMOUNT_TO_RAISE: u64 = 3;
 pub const SECONDS_TO_DAYS: i64 = 86400;
 pub const MAX_CONTRIBUTION_PERCENTAGE: u64 = 10;
-pub const PERCENTAGE_SCALER: u64 = 100;
\ No newline at end of file
+pub const PERCENTAGE_SCALER: u64 = 100;
+pub const MAX_BPS: u64 = 10_000;
+/// Raising this mint runs the campaign in native SOL, held as lamports in a vault PDA
+pub const NATIVE_MINT: Address = address!("So11111111111111111111111111111111111111112");
+pub const NATIVE_DECIMALS: u8 = 9;
\ No newline at end of file
@@ -36,5 +36,20 @@ fn process_instruction(
         ProgramInstruction::Refund => {
             instructions::refund::refund(accounts, instruction_data)
         }
+        ProgramInstruction::ClaimTranche => {
+            instructions::claim_tranche::claim_tranche(accounts, instruction_data)
+        }
+        ProgramInstruction::Cancel => {
+            instructions::cancel::cancel(accounts, instruction_data)
+        }
+        ProgramInstruction::Withdraw => {
+            instructions::withdraw::withdraw(accounts, instruction_data)
+        }
+        ProgramInstruction::Migrate => {
+            instructions::migrate::migrate(accounts, instruction_data)
+        }
+        ProgramInstruction::Reclaim => {
+            instructions::reclaim::reclaim(accounts, instruction_data)
+        }
     }
 }
\ No newline at end of file
@@ -12,7 +12,14 @@ pub enum FundraiserError {
     ContributePdaMismatch = 7,
     InvalidContributionAmount = 8,
     VaultOwnerMismatch = 9,
-    VaultAmountMismatch = 10
+    VaultAmountMismatch = 10,
+    FundraiserSucceeded = 11,
+    ReleaseScheduled = 12,
+    NothingToClaim = 13,
+    FundraiserCancelled = 14,
+    NotAllowlisted = 15,
+    RefundsOutstanding = 16,
+    FundraiserNotEnded = 17,
 }
 
 impl From<FundraiserError> for ProgramError {
@@ -0,0 +1,47 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+use pinocchio_pubkey::derive_address;
+use crate::errors::FundraiserError;
+use crate::states::Fundraiser;
+
+/// Lets the maker abandon a fundraiser. Contributions stop and every
+/// contributor can refund without waiting for the deadline.
+pub fn cancel(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
+    let [
+        maker,
+        fundraiser_acc,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fundraiser_state = Fundraiser::from_account_info(fundraiser_acc)?;
+
+    if fundraiser_state.maker() != *maker.address() {
+        return Err(FundraiserError::InvalidOwner.into());
+    }
+
+    let bump = fundraiser_state.bump;
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
+
+    let fundraiser_pda = derive_address(&seed, None, &crate::ID);
+    if fundraiser_pda != *fundraiser_acc.address().as_array() {
+        return Err(FundraiserError::PdaMismatch.into());
+    }
+
+    if fundraiser_state.is_cancelled() {
+        return Err(FundraiserError::FundraiserCancelled.into());
+    }
+
+    // Once tranches are paid out the vault no longer covers every refund
+    if fundraiser_state.claimed_amount() > 0 {
+        return Err(FundraiserError::FundraiserSucceeded.into());
+    }
+
+    fundraiser_state.set_cancelled();
+
+    Ok(())
+}
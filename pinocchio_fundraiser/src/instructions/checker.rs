@@ -1,8 +1,11 @@
 use pinocchio::cpi::{Seed, Signer};
 use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+use pinocchio::sysvars::{Sysvar, clock::Clock};
 use pinocchio_pubkey::derive_address;
 use crate::errors::FundraiserError;
-use crate::states::Fundraiser;
+use crate::states::{Fundraiser, ReleaseMode};
+use crate::token::{check_mint_program, check_token_account, close_vault, mint_decimals, TransferChecked};
+use crate::utils::{close_account, vault_balance};
 
 
 
@@ -15,8 +18,8 @@ pub fn checker(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
         mint_to_raise,
         fundraiser_acc,
         vault,
-        _token_program,
-        _system_program,
+        token_program,
+        system_program,
         _associated_token_program,
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
@@ -28,65 +31,85 @@ pub fn checker(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
 
     let fundraiser_state = Fundraiser::from_account_info(fundraiser_acc)?;
 
+    // Only the maker who started the fundraiser can claim it
+    if fundraiser_state.maker() != *maker.address() {
+        return Err(FundraiserError::InvalidOwner.into());
+    }
+
+    // Scheduled releases go through claim_tranche instead
+    if fundraiser_state.release_mode()? != ReleaseMode::Immediate {
+        return Err(FundraiserError::ReleaseScheduled.into());
+    }
+
+    if fundraiser_state.is_cancelled() {
+        return Err(FundraiserError::FundraiserCancelled.into());
+    }
+
     let bump = fundraiser_state.bump;
-    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &[bump]];
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
 
     let fundraiser_pda = derive_address(&seed, None, &crate::ID);
     if fundraiser_pda != *fundraiser_acc.address().as_array() {
         return Err(FundraiserError::PdaMismatch.into());
     }
 
-    let vault_state = pinocchio_token::state::TokenAccount::from_account_view(vault)?;
-    let mint_to_raise_state = pinocchio_token::state::Mint::from_account_view(mint_to_raise)?;
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    check_mint_program(mint_to_raise, token_program)?;
 
-    if vault_state.amount() != fundraiser_state.amount_to_raise() {
+    let native = fundraiser_state.is_native();
+    let raised = vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)?;
+
+    if !fundraiser_state.is_successful(raised, Clock::get()?.unix_timestamp) {
         return Err(FundraiserError::VaultAmountMismatch.into());
     }
 
+    // The lamport vault goes to the maker whole, raised SOL and rent alike
+    if native {
+        close_account(vault, maker)?;
+        close_account(fundraiser_acc, maker)?;
+        return Ok(());
+    }
+
+    let decimals = mint_decimals(mint_to_raise)?;
+
     if maker_ata.data_len() == 0 {
-        pinocchio_token::instructions::InitializeAccount3 {
+        pinocchio_associated_token_account::instructions::Create {
+            funding_account: maker,
             account: maker_ata,
+            wallet: maker,
             mint: mint_to_raise,
-            owner: maker.address(),
+            system_program,
+            token_program,
         }.invoke()?;
     }
+    check_token_account(maker_ata, mint_to_raise.address(), maker.address())?;
 
     let pda_bump_bytes = [bump];
-        // signer seeds
+    // signer seeds
     let signer_seeds = [
         Seed::from(b"fundraiser".as_ref()),
         Seed::from(maker.address().as_ref()),
+        Seed::from(&campaign_id_bytes[..]),
         Seed::from(&pda_bump_bytes[..]),
     ];
     let signers = [Signer::from(&signer_seeds[..])];
 
-    
- {
-       pinocchio_token::instructions::TransferChecked {
+    TransferChecked {
         from: vault,
         mint: mint_to_raise,
         to: maker_ata,
         authority: fundraiser_acc,
-        amount: fundraiser_state.amount_to_raise(),
-        decimals: mint_to_raise_state.decimals(),
+        token_program,
+        amount: raised,
+        decimals,
     }.invoke_signed(&signers)?;
- }
-
- {
-    pinocchio_token::instructions::CloseAccount {
-        account: vault,
-        destination: maker,
-        authority: fundraiser_acc
-    }.invoke_signed(&signers.clone())?;
- }
-
- {
-    pinocchio_system::instructions::Transfer {
-        from: fundraiser_acc,
-        to: maker,
-        lamports: fundraiser_acc.lamports(),
-    }.invoke_signed(&signers.clone())?;
- }
+
+    close_vault(vault, mint_to_raise, maker, fundraiser_acc, token_program, &signers)?;
+
+    close_account(fundraiser_acc, maker)?;
 
     Ok(())
 }
@@ -0,0 +1,126 @@
+use pinocchio::cpi::{Seed, Signer};
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+use pinocchio::sysvars::{Sysvar, clock::Clock};
+use pinocchio_pubkey::derive_address;
+use crate::errors::FundraiserError;
+use crate::states::Fundraiser;
+use crate::token::{check_mint_program, check_token_account, close_vault, mint_decimals, TransferChecked};
+use crate::utils::{close_account, move_lamports, vault_balance};
+
+/// Pays the maker whatever the release schedule has unlocked since the last
+/// claim. The last claim empties the vault and closes it with the fundraiser.
+pub fn claim_tranche(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
+    let [
+        maker,
+        maker_ata,
+        mint_to_raise,
+        fundraiser_acc,
+        vault,
+        token_program,
+        system_program,
+        _associated_token_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !maker.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fundraiser_state = Fundraiser::from_account_info(fundraiser_acc)?;
+
+    if fundraiser_state.maker() != *maker.address() {
+        return Err(FundraiserError::InvalidOwner.into());
+    }
+
+    if fundraiser_state.is_cancelled() {
+        return Err(FundraiserError::FundraiserCancelled.into());
+    }
+
+    let bump = fundraiser_state.bump;
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
+
+    let fundraiser_pda = derive_address(&seed, None, &crate::ID);
+    if fundraiser_pda != *fundraiser_acc.address().as_array() {
+        return Err(FundraiserError::PdaMismatch.into());
+    }
+
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    check_mint_program(mint_to_raise, token_program)?;
+
+    let native = fundraiser_state.is_native();
+    let remaining = vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)?;
+
+    let claimed = fundraiser_state.claimed_amount();
+    let raised = claimed.checked_add(remaining).ok_or(ProgramError::ArithmeticOverflow)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    if !fundraiser_state.is_successful(raised, now) {
+        return Err(FundraiserError::VaultAmountMismatch.into());
+    }
+
+    let amount = fundraiser_state
+        .unlocked_amount(raised, now)?
+        .saturating_sub(claimed);
+    if amount == 0 {
+        return Err(FundraiserError::NothingToClaim.into());
+    }
+
+    if native {
+        if amount < remaining {
+            move_lamports(vault, maker, amount)?;
+            fundraiser_state.set_claimed_amount(claimed + amount);
+        } else {
+            close_account(vault, maker)?;
+            close_account(fundraiser_acc, maker)?;
+        }
+        return Ok(());
+    }
+
+    let decimals = mint_decimals(mint_to_raise)?;
+
+    if maker_ata.data_len() == 0 {
+        pinocchio_associated_token_account::instructions::Create {
+            funding_account: maker,
+            account: maker_ata,
+            wallet: maker,
+            mint: mint_to_raise,
+            system_program,
+            token_program,
+        }.invoke()?;
+    }
+    check_token_account(maker_ata, mint_to_raise.address(), maker.address())?;
+
+    let pda_bump_bytes = [bump];
+    let signer_seeds = [
+        Seed::from(b"fundraiser".as_ref()),
+        Seed::from(maker.address().as_ref()),
+        Seed::from(&campaign_id_bytes[..]),
+        Seed::from(&pda_bump_bytes[..]),
+    ];
+    let signers = [Signer::from(&signer_seeds[..])];
+
+    TransferChecked {
+        from: vault,
+        mint: mint_to_raise,
+        to: maker_ata,
+        authority: fundraiser_acc,
+        token_program,
+        amount,
+        decimals,
+    }.invoke_signed(&signers)?;
+
+    if amount < remaining {
+        fundraiser_state.set_claimed_amount(claimed + amount);
+        return Ok(());
+    }
+
+    close_vault(vault, mint_to_raise, maker, fundraiser_acc, token_program, &signers)?;
+
+    close_account(fundraiser_acc, maker)?;
+
+    Ok(())
+}
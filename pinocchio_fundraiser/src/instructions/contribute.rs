@@ -1,10 +1,13 @@
 use pinocchio::{AccountView, ProgramResult, error::ProgramError, Address};
 use pinocchio::sysvars::{Sysvar, rent::Rent, clock::Clock};
 use pinocchio_pubkey::derive_address;
-use crate::{constants::{MAX_CONTRIBUTION_PERCENTAGE, PERCENTAGE_SCALER}, states::{ContributeState, Fundraiser}};
+use crate::states::{ContributeState, Fundraiser};
 use crate::errors::FundraiserError;
+use crate::merkle::{leaf_hash, verify_proof};
+use crate::token::{check_mint_program, check_token_account, mint_decimals, TransferChecked};
+use crate::utils::vault_balance;
 use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::instructions::TransferChecked;
+use pinocchio_token::instructions::MintTo;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Contribute {
@@ -21,8 +24,9 @@ pub fn contribute(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         fundraiser_acc,
         vault,
         maker,
-        _token_program,
-        _system_program
+        token_program,
+        _system_program,
+        receipt_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -33,28 +37,84 @@ pub fn contribute(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
 
     let fundraiser_state = Fundraiser::from_account_info(fundraiser_acc)?;
 
+    if fundraiser_state.is_cancelled() {
+        return Err(FundraiserError::FundraiserCancelled.into());
+    }
+
     let clock = Clock::get()?;
 
     let bump = fundraiser_state.bump;
-    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &[bump]];
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
 
     let fundraiser_pda = derive_address(&seed, None, &crate::ID);
     if fundraiser_pda != *fundraiser_acc.address().as_array() {
         return Err(FundraiserError::PdaMismatch.into());
     }
 
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+
+    check_mint_program(mint_to_raise, token_program)?;
+
+    let native = fundraiser_state.is_native();
+    if !native {
+        check_token_account(contributer_ata, mint_to_raise.address(), contributor.address())?;
+    }
+    let vault_before = vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)?;
+
     // Parse contribution amount from instruction data
-    if data.is_empty() {
+    if data.len() < 9 {
         return Err(FundraiserError::InvalidContributionAmount.into());
     }
-    let bump_contribute = data[0];
-    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let (contribution_data, allowlist_data) = data.split_at(9);
+    let bump_contribute = contribution_data[0];
+    let amount = u64::from_le_bytes(contribution_data[1..9].try_into().unwrap());
+
+    // The contribute account must be the contributor's own, an existing one
+    // keeps the bump it was created with
+    let contribute_bump = if contributer_account.data_len() == 0 {
+        bump_contribute
+    } else {
+        ContributeState::from_account_info(contributer_account)?.bump
+    };
+    let contribute_seed = [
+        b"contribute".as_ref(),
+        contributor.address().as_ref(),
+        fundraiser_acc.address().as_ref(),
+        &[contribute_bump],
+    ];
+    let contribute_pda = derive_address(&contribute_seed, None, &crate::ID);
+    if contribute_pda != *contributer_account.address().as_array() {
+        return Err(FundraiserError::ContributePdaMismatch.into());
+    }
 
+    if amount < fundraiser_state.min_contribution() {
+        return Err(FundraiserError::InvalidContributionAmount.into());
+    }
 
-    let mint_state = pinocchio_token::state::Mint::from_account_view(mint_to_raise)?;
-    let max_contribution = (fundraiser_state.amount_to_raise() * MAX_CONTRIBUTION_PERCENTAGE) / PERCENTAGE_SCALER;
+    // An allowlisted wallet is capped at the allocation its leaf commits to
+    // instead of the campaign-wide cap (data: max_allocation: u64, proof: [u8; 32] * n)
+    let max_contribution = if fundraiser_state.has_allowlist() {
+        let (max_allocation, proof) = allowlist_data
+            .split_first_chunk::<8>()
+            .ok_or(FundraiserError::NotAllowlisted)?;
+        let max_allocation = u64::from_le_bytes(*max_allocation);
+        // A trailing partial node would otherwise be dropped silently
+        if proof.len() % 32 != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let leaf = leaf_hash(contributor.address(), max_allocation);
+        if !verify_proof(proof, leaf, &fundraiser_state.allowlist_root()) {
+            return Err(FundraiserError::NotAllowlisted.into());
+        }
+        max_allocation
+    } else {
+        fundraiser_state.max_contribution()
+    };
 
-    // Check if contribution amount exceeds max allowed (10% of total raise)
+    // Check if contribution amount exceeds the per-wallet cap
     if amount > max_contribution {
         return Err(FundraiserError::ContributionExceedsMax.into());
     }
@@ -65,19 +125,48 @@ pub fn contribute(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         return Err(FundraiserError::FundraiserExpired.into());
     }
 
-    // Check if contribution would exceed the fundraising goal
-    if fundraiser_state.current_amount() + amount > fundraiser_state.amount_to_raise() {
+    // Check if contribution would exceed the hard cap
+    if fundraiser_state.current_amount() + amount > fundraiser_state.hard_cap() {
         return Err(FundraiserError::ContributionExceedsMax.into());
     }
 
+    // Transfer the contribution from contributor to vault
+    if native {
+        pinocchio_system::instructions::Transfer {
+            from: contributor,
+            to: vault,
+            lamports: amount,
+        }
+        .invoke()?;
+    } else {
+        let decimals = mint_decimals(mint_to_raise)?;
+
+        TransferChecked {
+            from: contributer_ata,
+            mint: mint_to_raise,
+            to: vault,
+            authority: contributor,
+            token_program,
+            amount,
+            decimals,
+        }
+        .invoke()?;
+    }
+
+    // A Token-2022 transfer fee is withheld from what the vault receives,
+    // only the rest counts towards the campaign
+    let received = vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)? - vault_before;
+
     // Create or update contributor account
     if contributer_account.data_len() == 0 {
 
         let rent = Rent::get()?;
+        let bump_bytes = [bump_contribute];
         let signer_seeds = [
             pinocchio::cpi::Seed::from(b"contribute".as_ref()),
             pinocchio::cpi::Seed::from(contributor.address().as_ref()),
             pinocchio::cpi::Seed::from(fundraiser_acc.address().as_ref()),
+            pinocchio::cpi::Seed::from(&bump_bytes[..]),
         ];
         let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
 
@@ -91,12 +180,12 @@ pub fn contribute(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         .invoke_signed(&signers)?;
 
         // Initialize the contribute state
-        let contribute_state = ContributeState::from_account_info(contributer_account)?;
-        contribute_state.set_inner(amount, bump_contribute);
+        let contribute_state = ContributeState::init_account_info(contributer_account)?;
+        contribute_state.set_inner(received, bump_contribute);
     } else {
         // Update existing contributor account
         let contribute_state = ContributeState::from_account_info(contributer_account)?;
-        let new_amount = contribute_state.amount() + amount;
+        let new_amount = contribute_state.amount() + received;
 
         // Check if new total exceeds max contribution limit
         if new_amount > max_contribution {
@@ -106,19 +195,35 @@ pub fn contribute(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         contribute_state.set_amount(new_amount);
     }
 
-    // Transfer tokens from contributor to vault
-    TransferChecked {
-        from: contributer_ata,
-        mint: mint_to_raise,
-        to: vault,
-        authority: contributor,
-        amount,
-        decimals: mint_state.decimals(),
-    }
-    .invoke()?;
-
     // Update fundraiser current amount
-    fundraiser_state.set_current_amount(fundraiser_state.current_amount() + amount);
+    fundraiser_state.set_current_amount(fundraiser_state.current_amount() + received);
+
+    if fundraiser_state.receipt_rate() > 0 {
+        let [receipt_mint, contributor_receipt_ata, ..] = receipt_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if fundraiser_state.receipt_mint() != *receipt_mint.address() {
+            return Err(FundraiserError::MintMismatch.into());
+        }
+        check_token_account(contributor_receipt_ata, receipt_mint.address(), contributor.address())?;
+
+        let pda_bump_bytes = [bump];
+        let signer_seeds = [
+            pinocchio::cpi::Seed::from(b"fundraiser".as_ref()),
+            pinocchio::cpi::Seed::from(maker.address().as_ref()),
+            pinocchio::cpi::Seed::from(&campaign_id_bytes[..]),
+            pinocchio::cpi::Seed::from(&pda_bump_bytes[..]),
+        ];
+        let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
+
+        MintTo {
+            mint: receipt_mint,
+            account: contributor_receipt_ata,
+            mint_authority: fundraiser_acc,
+            amount: fundraiser_state.receipts_for(received)?,
+        }
+        .invoke_signed(&signers)?;
+    }
 
     Ok(())
 }
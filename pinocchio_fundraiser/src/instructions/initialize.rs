@@ -10,10 +10,13 @@ use pinocchio::{
 use pinocchio_pubkey::derive_address;
 
 use pinocchio_system::instructions::CreateAccount;
-use pinocchio_token::instructions::InitializeAccount;
+use pinocchio_token::instructions::InitializeMint2;
 
-use crate::states::Fundraiser;
-use crate::constants::MIN_AMOUNT_TO_RAISE;
+use crate::errors::FundraiserError;
+use crate::states::{Fundraiser, ReleaseMode};
+use crate::constants::{MAX_BPS, MAX_CONTRIBUTION_PERCENTAGE, MIN_AMOUNT_TO_RAISE, NATIVE_DECIMALS, PERCENTAGE_SCALER};
+use crate::token::{check_mint_program, mint_decimals};
+use crate::utils::{check_token_vault, is_native_mint};
 
 pub fn initialize(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
     let [
@@ -21,8 +24,10 @@ pub fn initialize(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         fundraiser_acc,
         vault,
         mint_to_raise,
-        sysvar_rent_acc,
-        _system_program]
+        token_program,
+        system_program,
+        _associated_token_program,
+        receipt_accounts @ ..]
         = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -35,24 +40,111 @@ pub fn initialize(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
         return Err(ProgramError::AccountAlreadyInitialized);
     }
 
-    // Parse instruction data (bump: u8, amount: u64, duration: u64)
-    if data.len() < 17 {
+    // Parse instruction data (bump: u8, amount: u64, duration: u64, campaign_id: u64,
+    // vault bump: u8, of the lamport vault PDA or of the vault associated token account,
+    // optional min_threshold: u64,
+    // optional release schedule: mode: u8, period: u64, tranche_count: u8,
+    // optional receipts: rate: u64, receipt_mint_bump: u8,
+    // optional limits: wallet_cap_kind: u8, wallet_cap: u64, min_contribution: u64, hard_cap: u64,
+    // optional early exit penalty: u16 basis points,
+    // optional allowlist merkle root: [u8; 32])
+    if data.len() < 26 {
         return Err(ProgramError::InvalidInstructionData);
     }
     let bump = data[0];
     let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
-    let mint_to_raise_state = pinocchio_token::state::Mint::from_account_view(mint_to_raise)?;
+    check_mint_program(mint_to_raise, token_program)?;
+    let native = is_native_mint(mint_to_raise);
 
-    if amount < MIN_AMOUNT_TO_RAISE.pow(mint_to_raise_state.decimals() as u32) {
+    let decimals = if native {
+        NATIVE_DECIMALS
+    } else {
+        mint_decimals(mint_to_raise)?
+    };
+
+    // At least MIN_AMOUNT_TO_RAISE whole tokens
+    let min_amount_to_raise = 10u64
+        .checked_pow(decimals as u32)
+        .and_then(|unit| unit.checked_mul(MIN_AMOUNT_TO_RAISE))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if amount < min_amount_to_raise {
         return Err(ProgramError::InvalidInstructionData);
     }
 
     let duration = u64::from_le_bytes(data[9..17].try_into().unwrap());
+    let campaign_id = u64::from_le_bytes(data[17..25].try_into().unwrap());
+    let vault_bump = data[25];
+
+    // Without a threshold the maker can only ever claim a fully funded campaign
+    let min_threshold = match data.get(26..34) {
+        Some(bytes) => u64::from_le_bytes(bytes.try_into().unwrap()),
+        None => 0,
+    };
+    if min_threshold > amount {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Without a schedule checker hands out the whole vault at once
+    let (release_mode, release_period, tranche_count) = match data.get(34..44) {
+        Some(bytes) => (
+            ReleaseMode::try_from(bytes[0])?,
+            u64::from_le_bytes(bytes[1..9].try_into().unwrap()),
+            bytes[9],
+        ),
+        None => (ReleaseMode::Immediate, 0, 0),
+    };
+
+    // Contributors only get receipts back when the maker asks for a receipt mint
+    let (receipt_rate, receipt_bump) = match data.get(44..53) {
+        Some(bytes) => (u64::from_le_bytes(bytes[0..8].try_into().unwrap()), bytes[8]),
+        None => (0, 0),
+    };
+
+    // The wallet cap is either basis points of the goal (kind 0) or an absolute
+    // amount (kind 1). A hard cap of 0 stops contributions at the goal.
+    let (wallet_cap_kind, wallet_cap, min_contribution, hard_cap) = match data.get(53..78) {
+        Some(bytes) => (
+            bytes[0],
+            u64::from_le_bytes(bytes[1..9].try_into().unwrap()),
+            u64::from_le_bytes(bytes[9..17].try_into().unwrap()),
+            u64::from_le_bytes(bytes[17..25].try_into().unwrap()),
+        ),
+        None => (0, MAX_CONTRIBUTION_PERCENTAGE * MAX_BPS / PERCENTAGE_SCALER, 1, 0),
+    };
 
-    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &[bump]];
+    let max_contribution = match wallet_cap_kind {
+        0 if wallet_cap <= MAX_BPS => (amount as u128 * wallet_cap as u128 / MAX_BPS as u128) as u64,
+        1 => wallet_cap,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    let min_contribution = min_contribution.max(1);
+    let hard_cap = if hard_cap == 0 { amount } else { hard_cap };
+    if min_contribution > max_contribution || hard_cap < amount {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Without a penalty contributors withdraw early at no cost
+    let early_exit_penalty_bps = match data.get(78..80) {
+        Some(bytes) => u16::from_le_bytes(bytes.try_into().unwrap()),
+        None => 0,
+    };
+    if early_exit_penalty_bps as u64 > MAX_BPS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // An all-zero root leaves the campaign open to everyone
+    let allowlist_root: [u8; 32] = match data.get(80..112) {
+        Some(bytes) => bytes.try_into().unwrap(),
+        None => [0; 32],
+    };
+
+    let campaign_id_bytes = campaign_id.to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
 
     let fundraiser_pda = derive_address(&seed, None, &crate::ID);
-    assert_eq!(fundraiser_pda, *fundraiser_acc.address().as_array());
+    if fundraiser_pda != *fundraiser_acc.address().as_array() {
+        return Err(FundraiserError::PdaMismatch.into());
+    }
 
     let rent = Rent::get()?;
 
@@ -62,6 +154,7 @@ pub fn initialize(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
     let signer_seeds = [
         Seed::from(b"fundraiser".as_ref()),
         Seed::from(maker.address().as_ref()),
+        Seed::from(&campaign_id_bytes[..]),
         Seed::from(&pda_bump_bytes[..]),
     ];
     let signers = [Signer::from(&signer_seeds[..])];
@@ -75,16 +168,77 @@ pub fn initialize(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
     }
     .invoke_signed(&signers)?;
 
-    InitializeAccount {
-        account: vault,
-        mint: mint_to_raise,
-        owner: fundraiser_acc,
-        rent_sysvar: sysvar_rent_acc,
+    // SOL is held as lamports in a vault PDA owned by this program
+    if native {
+        let vault_bump_bytes = [vault_bump];
+        let vault_seeds = [
+            Seed::from(b"vault".as_ref()),
+            Seed::from(fundraiser_acc.address().as_ref()),
+            Seed::from(&vault_bump_bytes[..]),
+        ];
+
+        CreateAccount {
+            from: maker,
+            to: vault,
+            space: 0,
+            owner: &Address::from(crate::ID),
+            lamports: rent.try_minimum_balance(0)?,
+        }
+        .invoke_signed(&[Signer::from(&vault_seeds[..])])?;
+    } else {
+        // Tokens sit in the fundraiser PDA's associated token account
+        pinocchio_associated_token_account::instructions::Create {
+            funding_account: maker,
+            account: vault,
+            wallet: fundraiser_acc,
+            mint: mint_to_raise,
+            system_program,
+            token_program,
+        }
+        .invoke()?;
+        check_token_vault(vault, fundraiser_acc, mint_to_raise.address(), token_program, vault_bump)?;
     }
-    .invoke()?;
 
-    let fundraiser = Fundraiser::from_account_info(fundraiser_acc)?;
-    fundraiser.set_inner(maker.address(), mint_to_raise.address(), amount, duration, bump)?;
+    let fundraiser = Fundraiser::init_account_info(fundraiser_acc)?;
+    fundraiser.set_inner(maker.address(), campaign_id, mint_to_raise.address(), amount, duration, min_threshold, bump)?;
+    fundraiser.set_release_schedule(release_mode, release_period, tranche_count)?;
+    fundraiser.set_limits(max_contribution, min_contribution, hard_cap);
+    fundraiser.set_vault_bump(vault_bump);
+    fundraiser.set_early_exit_penalty_bps(early_exit_penalty_bps);
+    fundraiser.set_allowlist_root(allowlist_root);
+
+    if receipt_rate > 0 {
+        let [receipt_mint, ..] = receipt_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // Only the fundraiser PDA can ever mint or burn receipts
+        let receipt_bump_bytes = [receipt_bump];
+        let receipt_seeds = [
+            Seed::from(b"receipt".as_ref()),
+            Seed::from(fundraiser_acc.address().as_ref()),
+            Seed::from(&receipt_bump_bytes[..]),
+        ];
+
+        CreateAccount {
+            from: maker,
+            to: receipt_mint,
+            space: pinocchio_token::state::Mint::LEN as u64,
+            owner: &pinocchio_token::ID,
+            lamports: rent.try_minimum_balance(pinocchio_token::state::Mint::LEN)?,
+        }
+        .invoke_signed(&[Signer::from(&receipt_seeds[..])])?;
+
+        InitializeMint2 {
+            mint: receipt_mint,
+            decimals,
+            mint_authority: fundraiser_acc.address(),
+            freeze_authority: None,
+        }
+        .invoke()?;
+
+        fundraiser.set_receipt_mint(receipt_mint.address(), receipt_rate);
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use pinocchio::{AccountView, Address, ProgramResult, error::ProgramError};
+use pinocchio::sysvars::{Sysvar, rent::Rent};
+use pinocchio_pubkey::derive_address;
+use pinocchio_system::instructions::Transfer;
+use crate::errors::FundraiserError;
+use crate::states::{ContributeState, Fundraiser, HEADER_LEN};
+use crate::token::check_mint_program;
+use crate::utils::vault_balance;
+
+/// Moves a version 1 fundraiser or contribute account to the current layout, see the state
+/// module. Anyone can pay for it. A token fundraiser also takes its mint, vault and token
+/// program, and the bump of its vault ATA (data: vault_bump: u8), version 1 didn't store it.
+pub fn migrate(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
+    let [
+        payer,
+        account,
+        _system_program,
+        vault_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !account.owned_by(&Address::from(crate::ID)) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let old_len = account.data_len();
+    if old_len == Fundraiser::LEN_V1 {
+        grow(payer, account, Fundraiser::LEN, HEADER_LEN)?;
+    } else if old_len == ContributeState::LEN_V1 {
+        // A contribute record doesn't store its contributor, so there is no PDA to re-derive.
+        // Its length is the check: no fundraiser was ever that short.
+        grow(payer, account, ContributeState::LEN, HEADER_LEN)?;
+        return write_header(account, ContributeState::DISCRIMINATOR, ContributeState::VERSION);
+    } else {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    write_header(account, Fundraiser::DISCRIMINATOR, Fundraiser::VERSION)?;
+    let fundraiser_state = Fundraiser::from_account_info(account)?;
+
+    // Version 1 has nothing but its length to tell it apart, so its fields have to
+    // lead back to the account's own fundraiser PDA
+    let maker = fundraiser_state.maker();
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [
+        b"fundraiser".as_ref(),
+        maker.as_ref(),
+        &campaign_id_bytes,
+        &[fundraiser_state.bump],
+    ];
+    if derive_address(&seed, None, &crate::ID) != *account.address().as_array() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if fundraiser_state.is_native() {
+        return Ok(());
+    }
+
+    let [mint_to_raise, vault, token_program, ..] = vault_accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    check_mint_program(mint_to_raise, token_program)?;
+
+    // Only the canonical bump leads to an address that holds the vault
+    let vault_bump = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    fundraiser_state.set_vault_bump(vault_bump);
+    vault_balance(vault, account, fundraiser_state, token_program)?;
+
+    Ok(())
+}
+
+/// Tops up rent from `payer`, resizes `account` to `new_len` and moves its old
+/// fields `shift` bytes back
+fn grow(payer: &AccountView, account: &AccountView, new_len: usize, shift: usize) -> ProgramResult {
+    let old_len = account.data_len();
+
+    let rent_due = Rent::get()?
+        .try_minimum_balance(new_len)?
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: rent_due,
+        }
+        .invoke()?;
+    }
+
+    account.resize(new_len)?;
+
+    if shift > 0 {
+        account.try_borrow_mut()?.copy_within(0..old_len, shift);
+    }
+    Ok(())
+}
+
+fn write_header(account: &AccountView, discriminator: u8, version: u8) -> ProgramResult {
+    let mut data = account.try_borrow_mut()?;
+    data[0] = discriminator;
+    data[1] = version;
+    Ok(())
+}
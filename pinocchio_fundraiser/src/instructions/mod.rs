@@ -4,6 +4,11 @@ pub mod initialize;
 pub mod contribute;
 pub mod checker;
 pub mod refund;
+pub mod claim_tranche;
+pub mod cancel;
+pub mod withdraw;
+pub mod migrate;
+pub mod reclaim;
 
 pub use initialize::*;
 
@@ -12,7 +17,12 @@ pub enum ProgramInstruction {
     InitializeState,
     Contribute,
     Checker,
-    Refund
+    Refund,
+    ClaimTranche,
+    Cancel,
+    Withdraw,
+    Migrate,
+    Reclaim,
 }
 
 impl TryFrom<&u8> for ProgramInstruction {
@@ -24,6 +34,11 @@ impl TryFrom<&u8> for ProgramInstruction {
             1 => Ok(ProgramInstruction::Contribute),
             2 => Ok(ProgramInstruction::Checker),
             3 => Ok(ProgramInstruction::Refund),
+            4 => Ok(ProgramInstruction::ClaimTranche),
+            5 => Ok(ProgramInstruction::Cancel),
+            6 => Ok(ProgramInstruction::Withdraw),
+            7 => Ok(ProgramInstruction::Migrate),
+            8 => Ok(ProgramInstruction::Reclaim),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
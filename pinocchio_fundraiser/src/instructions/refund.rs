@@ -3,7 +3,9 @@ use pinocchio::sysvars::{Sysvar, clock::Clock};
 use pinocchio_pubkey::derive_address;
 use crate::errors::FundraiserError;
 use crate::states::{ContributeState, Fundraiser};
-use pinocchio_token::instructions::TransferChecked;
+use crate::token::{check_mint_program, check_token_account, mint_decimals, TransferChecked};
+use crate::utils::{close_account, move_lamports, vault_balance};
+use pinocchio_token::instructions::Burn;
 
 /// Refund instruction - allows contributors to get their tokens back if fundraiser duration is over without
 /// raising enough funds
@@ -16,7 +18,8 @@ pub fn refund(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
         fundraiser_acc,
         vault,
         maker,
-        _token_program,
+        token_program,
+        receipt_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -30,20 +33,50 @@ pub fn refund(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
 
     // Verify vault belongs to this fundraiser
     let bump = fundraiser_state.bump;
-    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &[bump]];
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
     let fundraiser_pda = derive_address(&seed, None, &crate::ID);
     if fundraiser_pda != *fundraiser_acc.address().as_array() {
-        return Err(ProgramError::InvalidInstructionData);
+        return Err(FundraiserError::PdaMismatch.into());
+    }
+
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    check_mint_program(mint_to_raise, token_program)?;
+    // Early exit penalties count towards the raise, so success is judged on the vault,
+    // together with what the maker already claimed from it
+    let raised = fundraiser_state
+        .claimed_amount()
+        .checked_add(vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)?)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let native = fundraiser_state.is_native();
+    if !native {
+        check_token_account(contributor_ata, mint_to_raise.address(), contributor.address())?;
+    }
+
+    // Once a tranche is paid out the rest of the vault belongs to the maker
+    if fundraiser_state.claimed_amount() > 0 {
+        return Err(FundraiserError::FundraiserSucceeded.into());
     }
 
     let clock = Clock::get()?;
 
     let current_time = clock.unix_timestamp;
 
-    // Check if fundraiser has expired (elapsed time > duration)
-    let elapsed_time = current_time - fundraiser_state.current_time();
-    if elapsed_time < fundraiser_state.duration() as i64 {
-        return Err(FundraiserError::FundraiserExpired.into());
+    // A cancelled fundraiser is refundable right away
+    if !fundraiser_state.is_cancelled() {
+        // Check if fundraiser has expired (elapsed time > duration)
+        let elapsed_time = current_time - fundraiser_state.current_time();
+        if elapsed_time < fundraiser_state.duration() as i64 {
+            return Err(FundraiserError::FundraiserExpired.into());
+        }
+
+        // A fundraiser the maker can claim is no longer refundable
+        if fundraiser_state.is_successful(raised, current_time) {
+            return Err(FundraiserError::FundraiserSucceeded.into());
+        }
     }
 
     // Get contributor's contribution amount
@@ -59,56 +92,63 @@ pub fn refund(accounts: &[AccountView], _data: &[u8]) -> ProgramResult {
         b"contribute".as_ref(),
         contributor.address().as_ref(),
         fundraiser_acc.address().as_ref(),
+        &[contribute_state.bump],
     ];
     let contribute_pda = derive_address(&contribute_seed, None, &crate::ID);
     if contribute_pda != *contribute_account.address().as_array() {
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    // Get mint decimals for transfer
-    let mint_state = pinocchio_token::state::Mint::from_account_view(mint_to_raise)?;
-
-    // Transfer tokens from vault back to contributor
-    let pda_bump_bytes = [bump];
-    let signer_seeds = [
-        pinocchio::cpi::Seed::from(b"fundraiser".as_ref()),
-        pinocchio::cpi::Seed::from(maker.address().as_ref()),
-        pinocchio::cpi::Seed::from(&pda_bump_bytes[..]),
-    ];
-    let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
-
-    TransferChecked {
-        from: vault,
-        mint: mint_to_raise,
-        to: contributor_ata,
-        authority: fundraiser_acc,
-        amount: refund_amount,
-        decimals: mint_state.decimals(),
+    // Receipts are the claim on the contribution, they have to be handed back first
+    if fundraiser_state.receipt_rate() > 0 {
+        let [receipt_mint, contributor_receipt_ata, ..] = receipt_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if fundraiser_state.receipt_mint() != *receipt_mint.address() {
+            return Err(FundraiserError::MintMismatch.into());
+        }
+
+        Burn {
+            account: contributor_receipt_ata,
+            mint: receipt_mint,
+            authority: contributor,
+            amount: fundraiser_state.receipts_for(refund_amount)?,
+        }
+        .invoke()?;
     }
-    .invoke_signed(&signers)?;
 
+    if native {
+        move_lamports(vault, contributor, refund_amount)?;
+    } else {
+        // Get mint decimals for transfer
+        let decimals = mint_decimals(mint_to_raise)?;
+
+        // Transfer tokens from vault back to contributor
+        let pda_bump_bytes = [bump];
+        let signer_seeds = [
+            pinocchio::cpi::Seed::from(b"fundraiser".as_ref()),
+            pinocchio::cpi::Seed::from(maker.address().as_ref()),
+            pinocchio::cpi::Seed::from(&campaign_id_bytes[..]),
+            pinocchio::cpi::Seed::from(&pda_bump_bytes[..]),
+        ];
+        let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
+
+        TransferChecked {
+            from: vault,
+            mint: mint_to_raise,
+            to: contributor_ata,
+            authority: fundraiser_acc,
+            token_program,
+            amount: refund_amount,
+            decimals,
+        }
+        .invoke_signed(&signers)?;
+    }
 
     fundraiser_state.set_current_amount(fundraiser_state.current_amount() - refund_amount);
 
-    // Reset contribution amount to zero
-    contribute_state.set_amount(0);
-
     // Close contribute account and return lamports to contributor
-    let bump_bytes = [contribute_state.bump];
-    let signer_seeds = [
-        pinocchio::cpi::Seed::from(b"contribute".as_ref()),
-        pinocchio::cpi::Seed::from(contributor.address().as_ref()),
-        pinocchio::cpi::Seed::from(fundraiser_acc.address().as_ref()),
-        pinocchio::cpi::Seed::from(&bump_bytes[..]),
-    ];
-    let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
-
-    pinocchio_system::instructions::Transfer {
-        from: contribute_account,
-        to: contributor,
-        lamports: contribute_account.lamports(),
-    }
-    .invoke_signed(&signers)?;
+    close_account(contribute_account, contributor)?;
 
     Ok(())
 }
@@ -0,0 +1,145 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+use pinocchio::sysvars::{Sysvar, clock::Clock};
+use pinocchio_pubkey::derive_address;
+use crate::errors::FundraiserError;
+use crate::states::{ContributeState, Fundraiser};
+use crate::token::{check_mint_program, check_token_account, mint_decimals, TransferChecked};
+use crate::utils::{close_account, move_lamports, vault_balance};
+use pinocchio_token::instructions::Burn;
+
+/// Withdraw instruction - lets a contributor take back part or all of their contribution while
+/// the fundraiser is still running. The early exit penalty stays in the vault and keeps counting
+/// towards the raise. It goes to the maker either way, through `reclaim` if the campaign fails.
+pub fn withdraw(accounts: &[AccountView], data: &[u8]) -> ProgramResult {
+    let [
+        contributor,
+        contribute_account,
+        mint_to_raise,
+        contributor_ata,
+        fundraiser_acc,
+        vault,
+        maker,
+        token_program,
+        receipt_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !contributor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fundraiser_state = Fundraiser::from_account_info(fundraiser_acc)?;
+
+    let bump = fundraiser_state.bump;
+    let campaign_id_bytes = fundraiser_state.campaign_id().to_le_bytes();
+    let seed = [b"fundraiser".as_ref(), maker.address().as_ref(), &campaign_id_bytes, &[bump]];
+    let fundraiser_pda = derive_address(&seed, None, &crate::ID);
+    if fundraiser_pda != *fundraiser_acc.address().as_array() {
+        return Err(FundraiserError::PdaMismatch.into());
+    }
+
+    // A cancelled fundraiser refunds in full instead
+    if fundraiser_state.is_cancelled() {
+        return Err(FundraiserError::FundraiserCancelled.into());
+    }
+
+    if Clock::get()?.unix_timestamp > fundraiser_state.end_time() {
+        return Err(FundraiserError::FundraiserExpired.into());
+    }
+
+    if fundraiser_state.mint_to_raise() != *mint_to_raise.address() {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    check_mint_program(mint_to_raise, token_program)?;
+    vault_balance(vault, fundraiser_acc, fundraiser_state, token_program)?;
+
+    let native = fundraiser_state.is_native();
+    if !native {
+        check_token_account(contributor_ata, mint_to_raise.address(), contributor.address())?;
+    }
+
+    let contribute_state = ContributeState::from_account_info(contribute_account)?;
+
+    let contribute_seed = [
+        b"contribute".as_ref(),
+        contributor.address().as_ref(),
+        fundraiser_acc.address().as_ref(),
+        &[contribute_state.bump],
+    ];
+    let contribute_pda = derive_address(&contribute_seed, None, &crate::ID);
+    if contribute_pda != *contribute_account.address().as_array() {
+        return Err(FundraiserError::ContributePdaMismatch.into());
+    }
+
+    // Parse withdrawal amount from instruction data
+    let amount = data
+        .get(0..8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(FundraiserError::InvalidContributionAmount)?;
+    let contributed = contribute_state.amount();
+    if amount == 0 || amount > contributed {
+        return Err(FundraiserError::InvalidContributionAmount.into());
+    }
+
+    let penalty = fundraiser_state.early_exit_penalty(amount);
+    let payout = amount - penalty;
+
+    // Receipts for the whole withdrawn amount are handed back, penalty included
+    if fundraiser_state.receipt_rate() > 0 {
+        let [receipt_mint, contributor_receipt_ata, ..] = receipt_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if fundraiser_state.receipt_mint() != *receipt_mint.address() {
+            return Err(FundraiserError::MintMismatch.into());
+        }
+
+        Burn {
+            account: contributor_receipt_ata,
+            mint: receipt_mint,
+            authority: contributor,
+            amount: fundraiser_state.receipts_for(amount)?,
+        }
+        .invoke()?;
+    }
+
+    if payout > 0 {
+        if native {
+            move_lamports(vault, contributor, payout)?;
+        } else {
+            let decimals = mint_decimals(mint_to_raise)?;
+
+            let pda_bump_bytes = [bump];
+            let signer_seeds = [
+                pinocchio::cpi::Seed::from(b"fundraiser".as_ref()),
+                pinocchio::cpi::Seed::from(maker.address().as_ref()),
+                pinocchio::cpi::Seed::from(&campaign_id_bytes[..]),
+                pinocchio::cpi::Seed::from(&pda_bump_bytes[..]),
+            ];
+            let signers = [pinocchio::cpi::Signer::from(&signer_seeds[..])];
+
+            TransferChecked {
+                from: vault,
+                mint: mint_to_raise,
+                to: contributor_ata,
+                authority: fundraiser_acc,
+                token_program,
+                amount: payout,
+                decimals,
+            }
+            .invoke_signed(&signers)?;
+        }
+    }
+
+    // current_amount only tracks what contributors can still refund, the
+    // penalty sits in the vault on top of it
+    fundraiser_state.set_current_amount(fundraiser_state.current_amount() - amount);
+
+    if amount == contributed {
+        close_account(contribute_account, contributor)?;
+    } else {
+        contribute_state.set_amount(contributed - amount);
+    }
+
+    Ok(())
+}
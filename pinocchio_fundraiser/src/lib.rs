@@ -10,5 +10,8 @@ pub mod errors;
 pub mod instructions;
 pub mod states;
 pub mod constants;
+pub mod utils;
+pub mod token;
+pub mod merkle;
 
 pinocchio_pubkey::declare_id!("E4U89BDRNy7Z6ZFaHPKz1VG8qk384jWv7Cgacp8F8x7X");
\ No newline at end of file
@@ -0,0 +1,29 @@
+use pinocchio::Address;
+use solana_sha256_hasher::hashv;
+
+/// Keeps a leaf from ever passing for an inner node and the other way round
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Allowlist leaf committing to a contributor and the most they may contribute
+pub fn leaf_hash(contributor: &Address, max_allocation: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, contributor.as_ref(), &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+/// Parent of two nodes. Children are hashed in sorted order, so proofs don't
+/// need to say which side each sibling is on.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Whether `proof`, the 32-byte sibling hashes from the leaf upwards, leads from `leaf` to `root`
+pub fn verify_proof(proof: &[u8], leaf: [u8; 32], root: &[u8; 32]) -> bool {
+    let siblings = proof.chunks_exact(32);
+    if !siblings.remainder().is_empty() {
+        return false;
+    }
+
+    let computed = siblings.fold(leaf, |node, sibling| node_hash(&node, sibling.try_into().unwrap()));
+    computed == *root
+}
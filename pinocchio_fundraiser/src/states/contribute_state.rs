@@ -1,30 +1,56 @@
 use pinocchio::{AccountView, error::ProgramError};
+use bytemuck::{Pod, Zeroable};
 use wincode::{SchemaRead, SchemaWrite};
+use super::HEADER_LEN;
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq, SchemaRead, SchemaWrite)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, SchemaRead, SchemaWrite)]
 pub struct ContributeState {
+    discriminator: u8,
+    version: u8,
     amount: [u8; 8],
     pub bump: u8,
 }
 
 impl ContributeState {
     pub const LEN: usize = core::mem::size_of::<Self>();
+    pub const DISCRIMINATOR: u8 = 2;
+    /// Version 1 had no discriminator or version in front of the fields
+    pub const VERSION: u8 = 2;
+    pub const LEN_V1: usize = Self::LEN - HEADER_LEN;
 
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
-        let mut data = account_info.try_borrow_mut()?;
-        if data.len() != ContributeState::LEN {
+        let state = Self::load(account_info)?;
+        if state.discriminator != Self::DISCRIMINATOR || state.version != Self::VERSION {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-            return Err(ProgramError::InvalidAccountData);
+        Ok(state)
+    }
+
+    /// Loads a freshly created account and stamps its discriminator and version
+    pub fn init_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
+        let state = Self::load(account_info)?;
+        if state.discriminator != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+        state.discriminator = Self::DISCRIMINATOR;
+        state.version = Self::VERSION;
+        Ok(state)
     }
 
- 
+    fn load(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
+        let mut data = account_info.try_borrow_mut()?;
+        let state = bytemuck::try_from_bytes_mut::<Self>(&mut data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        Ok(unsafe { &mut *(state as *mut Self) })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
 
     pub fn amount(&self) -> u64 {
         u64::from_le_bytes(self.amount)
@@ -1,32 +1,104 @@
 use pinocchio::{AccountView, Address, error::ProgramError, sysvars::clock::Clock, sysvars::Sysvar};
+use crate::constants::{MAX_BPS, NATIVE_MINT};
+use super::HEADER_LEN;
+use bytemuck::{Pod, Zeroable};
 use wincode::{SchemaRead, SchemaWrite};
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq, SchemaRead, SchemaWrite)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, SchemaRead, SchemaWrite)]
 pub struct Fundraiser {
+    discriminator: u8,
+    version: u8,
     maker: [u8; 32],
+    campaign_id: [u8; 8],
     mint_to_raise: [u8; 32],
     amount_to_raise: [u8; 8],
     current_amount: [u8; 8],
     duration: [u8; 8],
     current_time: [u8; 8],
+    min_threshold: [u8; 8],
+    release_period: [u8; 8],
+    claimed_amount: [u8; 8],
+    receipt_mint: [u8; 32],
+    receipt_rate: [u8; 8],
+    max_contribution: [u8; 8],
+    min_contribution: [u8; 8],
+    hard_cap: [u8; 8],
+    early_exit_penalty_bps: [u8; 2],
+    release_mode: u8,
+    tranche_count: u8,
+    cancelled: u8,
+    vault_bump: u8,
     pub bump: u8,
+    /// Not in version 1, at the end so those accounts migrate by growing
+    allowlist_root: [u8; 32],
+}
+
+/// How the raised funds are handed to the maker once the campaign succeeds
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReleaseMode {
+    /// Everything at once through `checker`
+    Immediate,
+    /// Unlocks linearly over `release_period` seconds after the deadline
+    Linear,
+    /// `tranche_count` equal tranches, the first at the deadline and one more
+    /// every `release_period` seconds
+    Tranches,
+}
+
+impl TryFrom<u8> for ReleaseMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ReleaseMode::Immediate),
+            1 => Ok(ReleaseMode::Linear),
+            2 => Ok(ReleaseMode::Tranches),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
 }
 
 impl Fundraiser {
     pub const LEN: usize = core::mem::size_of::<Self>();
+    pub const DISCRIMINATOR: u8 = 1;
+    /// Version 1 had no discriminator or version in front of the fields,
+    /// and no allowlist root at the end
+    pub const VERSION: u8 = 2;
+    pub const LEN_V1: usize = Self::LEN - HEADER_LEN - 32;
 
     pub fn from_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
-        let mut data = account_info.try_borrow_mut()?;
-        if data.len() != Fundraiser::LEN {
+        let state = Self::load(account_info)?;
+        if state.discriminator != Self::DISCRIMINATOR || state.version != Self::VERSION {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-            return Err(ProgramError::InvalidAccountData);
+        Ok(state)
+    }
+
+    /// Loads a freshly created account and stamps its discriminator and version
+    pub fn init_account_info(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
+        let state = Self::load(account_info)?;
+        if state.discriminator != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
         }
 
-        Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
+        state.discriminator = Self::DISCRIMINATOR;
+        state.version = Self::VERSION;
+        Ok(state)
+    }
+
+    fn load(account_info: &AccountView) -> Result<&mut Self, ProgramError> {
+        let mut data = account_info.try_borrow_mut()?;
+        let state = bytemuck::try_from_bytes_mut::<Self>(&mut data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        Ok(unsafe { &mut *(state as *mut Self) })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
     }
 
     pub fn maker(&self) -> Address {
@@ -37,6 +109,15 @@ impl Fundraiser {
         self.maker.copy_from_slice(maker.as_ref());
     }
 
+    /// Distinguishes the fundraisers of a single maker, part of the PDA seeds.
+    pub fn campaign_id(&self) -> u64 {
+        u64::from_le_bytes(self.campaign_id)
+    }
+
+    pub fn set_campaign_id(&mut self, campaign_id: u64) {
+        self.campaign_id = campaign_id.to_le_bytes();
+    }
+
     pub fn mint_to_raise(&self) -> Address {
         Address::from(self.mint_to_raise)
     }
@@ -45,6 +126,21 @@ impl Fundraiser {
         self.mint_to_raise.copy_from_slice(mint_a.as_ref());
     }
 
+    /// Whether the campaign raises native SOL into a lamport vault
+    pub fn is_native(&self) -> bool {
+        self.mint_to_raise == *NATIVE_MINT.as_array()
+    }
+
+    /// Bump of the lamport vault PDA, or of the vault associated token account
+    /// of a token campaign
+    pub fn vault_bump(&self) -> u8 {
+        self.vault_bump
+    }
+
+    pub fn set_vault_bump(&mut self, bump: u8) {
+        self.vault_bump = bump;
+    }
+
     pub fn amount_to_raise(&self) -> u64 {
         u64::from_le_bytes(self.amount_to_raise)
     }
@@ -53,6 +149,8 @@ impl Fundraiser {
         self.amount_to_raise = amount.to_le_bytes();
     }
 
+    /// Contributions not yet withdrawn or refunded. The vault also holds the
+    /// early exit penalties on top of it.
     pub fn current_amount(&self) -> u64 {
         u64::from_le_bytes(self.current_amount)
     }
@@ -78,14 +176,195 @@ impl Fundraiser {
         Ok(())
     }
 
-    pub fn set_inner(&mut self, maker: &Address, mint_to_raise: &Address, amount_to_raise: u64, duration: u64, bump: u8) -> Result<(), ProgramError> {
+    /// Amount that lets the maker claim once the deadline has passed, even
+    /// though `amount_to_raise` wasn't reached. 0 disables the fallback.
+    pub fn min_threshold(&self) -> u64 {
+        u64::from_le_bytes(self.min_threshold)
+    }
+
+    pub fn set_min_threshold(&mut self, amount: u64) {
+        self.min_threshold = amount.to_le_bytes();
+    }
+
+    pub fn release_mode(&self) -> Result<ReleaseMode, ProgramError> {
+        ReleaseMode::try_from(self.release_mode)
+    }
+
+    pub fn release_period(&self) -> u64 {
+        u64::from_le_bytes(self.release_period)
+    }
+
+    pub fn tranche_count(&self) -> u8 {
+        self.tranche_count
+    }
+
+    pub fn set_release_schedule(&mut self, mode: ReleaseMode, period: u64, tranche_count: u8) -> Result<(), ProgramError> {
+        let valid = match mode {
+            ReleaseMode::Immediate => period == 0 && tranche_count == 0,
+            ReleaseMode::Linear => period > 0 && tranche_count == 0,
+            ReleaseMode::Tranches => period > 0 && tranche_count > 0,
+        };
+        if !valid {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        self.release_mode = mode as u8;
+        self.release_period = period.to_le_bytes();
+        self.tranche_count = tranche_count;
+        Ok(())
+    }
+
+    /// Amount the maker already took out of the vault through `claim_tranche`
+    pub fn claimed_amount(&self) -> u64 {
+        u64::from_le_bytes(self.claimed_amount)
+    }
+
+    pub fn set_claimed_amount(&mut self, amount: u64) {
+        self.claimed_amount = amount.to_le_bytes();
+    }
+
+    /// Part of `total` the release schedule has unlocked at `now`, claimed or not.
+    /// Scheduled releases start at the deadline, even if the goal was met earlier.
+    pub fn unlocked_amount(&self, total: u64, now: i64) -> Result<u64, ProgramError> {
+        let mode = self.release_mode()?;
+        if mode == ReleaseMode::Immediate {
+            return Ok(total);
+        }
+
+        let end_time = self.end_time();
+        if now < end_time {
+            return Ok(0);
+        }
+        let elapsed = (now - end_time) as u64;
+        let period = self.release_period();
+
+        let (unlocked, out_of) = match mode {
+            ReleaseMode::Linear => (elapsed.min(period), period),
+            _ => {
+                let count = self.tranche_count as u64;
+                ((elapsed / period + 1).min(count), count)
+            }
+        };
+
+        Ok((total as u128 * unlocked as u128 / out_of as u128) as u64)
+    }
+
+    pub fn receipt_mint(&self) -> Address {
+        Address::from(self.receipt_mint)
+    }
+
+    /// Receipts minted per raised token, 0 when the fundraiser has no receipt mint
+    pub fn receipt_rate(&self) -> u64 {
+        u64::from_le_bytes(self.receipt_rate)
+    }
+
+    pub fn set_receipt_mint(&mut self, receipt_mint: &Address, rate: u64) {
+        self.receipt_mint.copy_from_slice(receipt_mint.as_ref());
+        self.receipt_rate = rate.to_le_bytes();
+    }
+
+    pub fn receipts_for(&self, amount: u64) -> Result<u64, ProgramError> {
+        amount
+            .checked_mul(self.receipt_rate())
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Most a single wallet can contribute over the whole campaign
+    pub fn max_contribution(&self) -> u64 {
+        u64::from_le_bytes(self.max_contribution)
+    }
+
+    pub fn min_contribution(&self) -> u64 {
+        u64::from_le_bytes(self.min_contribution)
+    }
+
+    /// Most the campaign accepts in total, at least `amount_to_raise`
+    pub fn hard_cap(&self) -> u64 {
+        u64::from_le_bytes(self.hard_cap)
+    }
+
+    pub fn set_limits(&mut self, max_contribution: u64, min_contribution: u64, hard_cap: u64) {
+        self.max_contribution = max_contribution.to_le_bytes();
+        self.min_contribution = min_contribution.to_le_bytes();
+        self.hard_cap = hard_cap.to_le_bytes();
+    }
+
+    /// Share of a withdrawal kept in the vault, in basis points
+    pub fn early_exit_penalty_bps(&self) -> u16 {
+        u16::from_le_bytes(self.early_exit_penalty_bps)
+    }
+
+    pub fn set_early_exit_penalty_bps(&mut self, bps: u16) {
+        self.early_exit_penalty_bps = bps.to_le_bytes();
+    }
+
+    /// Part of a withdrawal of `amount` that stays in the vault
+    pub fn early_exit_penalty(&self, amount: u64) -> u64 {
+        (amount as u128 * self.early_exit_penalty_bps() as u128 / MAX_BPS as u128) as u64
+    }
+
+    /// Merkle root of the (contributor, max_allocation) leaves allowed to contribute
+    pub fn allowlist_root(&self) -> [u8; 32] {
+        self.allowlist_root
+    }
+
+    /// Whether only allowlisted wallets can contribute, each up to its own allocation
+    pub fn has_allowlist(&self) -> bool {
+        self.allowlist_root != [0; 32]
+    }
+
+    pub fn set_allowlist_root(&mut self, root: [u8; 32]) {
+        self.allowlist_root = root;
+    }
+
+    /// Set by the maker through `cancel`, opens refunds right away
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled != 0
+    }
+
+    pub fn set_cancelled(&mut self) {
+        self.cancelled = 1;
+    }
+
+    pub fn end_time(&self) -> i64 {
+        self.current_time() + (self.duration() as i64)
+    }
+
+    /// Whether `raised` lets the maker claim at `now`: the goal is met, or the
+    /// deadline has passed and the minimum threshold, if any, is met.
+    pub fn is_successful(&self, raised: u64, now: i64) -> bool {
+        if raised >= self.amount_to_raise() {
+            return true;
+        }
+
+        let min_threshold = self.min_threshold();
+        min_threshold > 0 && now >= self.end_time() && raised >= min_threshold
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(&mut self, maker: &Address, campaign_id: u64, mint_to_raise: &Address, amount_to_raise: u64, duration: u64, min_threshold: u64, bump: u8) -> Result<(), ProgramError> {
         self.maker.copy_from_slice(maker.as_ref());
+        self.campaign_id = campaign_id.to_le_bytes();
         self.mint_to_raise.copy_from_slice(mint_to_raise.as_ref());
         self.amount_to_raise = amount_to_raise.to_le_bytes();
         self.current_amount = [0; 8];
         self.duration = duration.to_le_bytes();
         self.current_time = Clock::get()?.unix_timestamp.to_le_bytes();
+        self.min_threshold = min_threshold.to_le_bytes();
+        self.release_period = [0; 8];
+        self.claimed_amount = [0; 8];
+        self.receipt_mint = [0; 32];
+        self.receipt_rate = [0; 8];
+        self.max_contribution = [0; 8];
+        self.min_contribution = [0; 8];
+        self.hard_cap = amount_to_raise.to_le_bytes();
+        self.early_exit_penalty_bps = [0; 2];
+        self.release_mode = ReleaseMode::Immediate as u8;
+        self.tranche_count = 0;
+        self.cancelled = 0;
+        self.vault_bump = 0;
         self.bump = bump;
+        self.allowlist_root = [0; 32];
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,5 +1,19 @@
+//! Account layouts. Every account starts with a discriminator and a version byte.
+//!
+//! `migrate` brings version 1 accounts, written before the header existed, to the current
+//! version:
+//! - A Fundraiser ([`Fundraiser::LEN_V1`]) has its fields shifted behind the header and an empty
+//!   allowlist root appended. It is recognised by its length, then its maker, campaign id and
+//!   bump have to re-derive the account's own PDA. A token fundraiser stored no vault bump, the
+//!   caller passes the bump of the vault ATA and `migrate` checks it against the vault.
+//! - A ContributeState ([`ContributeState::LEN_V1`]) has its fields shifted behind the header.
+//!   It has no other field to check, no fundraiser was ever 9 bytes.
+
 pub mod fundraiser_state;
 pub mod contribute_state;
 
 pub use fundraiser_state::*;
 pub use contribute_state::ContributeState;
+
+/// Discriminator and version bytes in front of every account
+pub const HEADER_LEN: usize = 2;
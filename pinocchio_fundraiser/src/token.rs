@@ -0,0 +1,262 @@
+use pinocchio::{
+    AccountView, Address, ProgramResult,
+    address::address,
+    cpi::{invoke_signed_with_bounds, Signer},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+};
+use crate::errors::FundraiserError;
+use crate::utils::is_native_mint;
+
+pub const TOKEN_PROGRAM_ID: Address = pinocchio_token::ID;
+pub const TOKEN_2022_PROGRAM_ID: Address = address!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+const MINT_LEN: usize = 82;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+/// Token-2022 writes the account type right after the base account layout,
+/// followed by the extension TLV entries.
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const EXTENSION_TLV_START: usize = TOKEN_ACCOUNT_LEN + 1;
+const EXTENSION_TRANSFER_FEE_AMOUNT: u16 = 2;
+
+/// Ensures `token_program` is the legacy Token or Token-2022 program and that it
+/// owns `mint`. A SOL campaign passes the system program instead.
+pub fn check_mint_program(mint: &AccountView, token_program: &AccountView) -> ProgramResult {
+    let program_id = token_program.address();
+    if is_native_mint(mint) {
+        if program_id != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        return Ok(());
+    }
+
+    if program_id != &TOKEN_PROGRAM_ID && program_id != &TOKEN_2022_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !mint.owned_by(program_id) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    Ok(())
+}
+
+fn owned_by_token_program(account: &AccountView) -> bool {
+    account.owned_by(&TOKEN_PROGRAM_ID) || account.owned_by(&TOKEN_2022_PROGRAM_ID)
+}
+
+/// Decimals of a legacy Token or Token-2022 mint.
+pub fn mint_decimals(mint: &AccountView) -> Result<u8, ProgramError> {
+    if !owned_by_token_program(mint) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let data = mint.try_borrow()?;
+    if data.len() < MINT_LEN || data[45] != 1 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(data[44])
+}
+
+/// The fields of a legacy Token or Token-2022 account the fundraiser cares about.
+/// Token-2022 accounts may carry extensions past the base layout.
+pub struct TokenAccountState {
+    pub mint: Address,
+    pub owner: Address,
+    pub amount: u64,
+}
+
+impl TokenAccountState {
+    pub fn from_account_view(account: &AccountView) -> Result<Self, ProgramError> {
+        if !owned_by_token_program(account) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let data = account.try_borrow()?;
+        let valid_len = data.len() == TOKEN_ACCOUNT_LEN
+            || (data.len() > TOKEN_ACCOUNT_LEN
+                && account.owned_by(&TOKEN_2022_PROGRAM_ID)
+                && data[TOKEN_ACCOUNT_LEN] == ACCOUNT_TYPE_ACCOUNT);
+        if !valid_len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut amount = [0u8; 8];
+        amount.copy_from_slice(&data[64..72]);
+
+        Ok(Self {
+            mint: Address::new_from_array(data[0..32].try_into().unwrap()),
+            owner: Address::new_from_array(data[32..64].try_into().unwrap()),
+            amount: u64::from_le_bytes(amount),
+        })
+    }
+}
+
+/// Reads `account` and ensures it holds `mint` on behalf of `owner`.
+pub fn check_token_account(
+    account: &AccountView,
+    mint: &Address,
+    owner: &Address,
+) -> Result<TokenAccountState, ProgramError> {
+    let state = TokenAccountState::from_account_view(account)?;
+    if state.mint != *mint {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    if state.owner != *owner {
+        return Err(FundraiserError::InvalidOwner.into());
+    }
+    Ok(state)
+}
+
+/// `TransferChecked` against whichever token program owns the mint.
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountView,
+    pub mint: &'a AccountView,
+    pub to: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TransferChecked<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.from.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::writable(self.to.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &instruction_accounts,
+            data: &data,
+        };
+
+        invoke_signed_with_bounds::<4>(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+/// Transfer fees withheld in a Token-2022 account. Such an account can't be
+/// closed until the fees are harvested to the mint.
+pub fn withheld_transfer_fees(account: &AccountView) -> Result<u64, ProgramError> {
+    if !account.owned_by(&TOKEN_2022_PROGRAM_ID) {
+        return Ok(0);
+    }
+
+    let data = account.try_borrow()?;
+    let mut offset = EXTENSION_TLV_START;
+    while offset + 4 <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        let value = data
+            .get(offset + 4..offset + 4 + length)
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        if extension_type == EXTENSION_TRANSFER_FEE_AMOUNT {
+            let withheld: [u8; 8] = value
+                .get(..8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(ProgramError::InvalidAccountData)?;
+            return Ok(u64::from_le_bytes(withheld));
+        }
+
+        offset += 4 + length;
+    }
+
+    Ok(0)
+}
+
+/// Token-2022 `HarvestWithheldTokensToMint` for a single account. Permissionless.
+pub struct HarvestWithheldTokensToMint<'a> {
+    pub mint: &'a AccountView,
+    pub account: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl HarvestWithheldTokensToMint<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.mint.address()),
+            InstructionAccount::writable(self.account.address()),
+        ];
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &instruction_accounts,
+            // TransferFeeExtension, HarvestWithheldTokensToMint
+            data: &[26, 4],
+        };
+
+        invoke_signed_with_bounds::<2>(&instruction, &[self.mint, self.account], &[])
+    }
+}
+
+/// `CloseAccount` against whichever token program owns the account.
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountView,
+    pub destination: &'a AccountView,
+    pub authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.account.address()),
+            InstructionAccount::writable(self.destination.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        let instruction = InstructionView {
+            program_id: self.token_program.address(),
+            accounts: &instruction_accounts,
+            data: &[9],
+        };
+
+        invoke_signed_with_bounds::<3>(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
+
+/// Closes an emptied vault, first sweeping any withheld Token-2022 transfer
+/// fees to the mint so the close isn't rejected.
+pub fn close_vault(
+    vault: &AccountView,
+    mint: &AccountView,
+    destination: &AccountView,
+    authority: &AccountView,
+    token_program: &AccountView,
+    signers: &[Signer],
+) -> ProgramResult {
+    if withheld_transfer_fees(vault)? > 0 {
+        HarvestWithheldTokensToMint {
+            mint,
+            account: vault,
+            token_program,
+        }.invoke()?;
+    }
+
+    CloseAccount {
+        account: vault,
+        destination,
+        authority,
+        token_program,
+    }.invoke_signed(signers)
+}
@@ -0,0 +1,105 @@
+use pinocchio::{AccountView, Address, ProgramResult, error::ProgramError};
+use pinocchio::sysvars::{Sysvar, rent::Rent};
+use pinocchio_pubkey::derive_address;
+use crate::constants::NATIVE_MINT;
+use crate::errors::FundraiserError;
+use crate::states::Fundraiser;
+use crate::token::TokenAccountState;
+
+/// Closes an account owned by this program: moves all of its lamports to
+/// `destination`, shrinks its data to zero and hands it back to the system program.
+pub fn close_account(account: &AccountView, destination: &AccountView) -> ProgramResult {
+    let lamports = destination
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    destination.set_lamports(lamports);
+    account.set_lamports(0);
+
+    account.resize(0)?;
+    unsafe {
+        account.assign(&pinocchio_system::ID);
+    }
+
+    Ok(())
+}
+
+pub fn is_native_mint(mint: &AccountView) -> bool {
+    mint.address() == &NATIVE_MINT
+}
+
+/// Moves lamports out of an account owned by this program, such as the
+/// lamport vault of a SOL campaign.
+pub fn move_lamports(from: &AccountView, to: &AccountView, amount: u64) -> ProgramResult {
+    let from_lamports = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let to_lamports = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    from.set_lamports(from_lamports);
+    to.set_lamports(to_lamports);
+    Ok(())
+}
+
+/// Ensures `vault` is the lamport vault PDA of `fundraiser`
+pub fn check_lamport_vault(vault: &AccountView, fundraiser: &AccountView, bump: u8) -> ProgramResult {
+    let seed = [b"vault".as_ref(), fundraiser.address().as_ref(), &[bump]];
+    if derive_address(&seed, None, &crate::ID) != *vault.address().as_array() {
+        return Err(FundraiserError::VaultOwnerMismatch.into());
+    }
+    Ok(())
+}
+
+/// Ensures `vault` is the associated token account of `fundraiser` for `mint`
+/// under `token_program`, so a token account the fundraiser merely owns can't
+/// stand in for it
+pub fn check_token_vault(
+    vault: &AccountView,
+    fundraiser: &AccountView,
+    mint: &Address,
+    token_program: &AccountView,
+    bump: u8,
+) -> ProgramResult {
+    let seed = [fundraiser.address().as_ref(), token_program.address().as_ref(), mint.as_ref(), &[bump]];
+    if derive_address(&seed, None, pinocchio_associated_token_account::ID.as_array()) != *vault.address().as_array() {
+        return Err(FundraiserError::VaultOwnerMismatch.into());
+    }
+    Ok(())
+}
+
+/// What the vault of `fundraiser` holds for the campaign: the lamports of a
+/// lamport vault on top of its rent, or the balance of its token account.
+/// Fails if `vault` isn't that vault.
+pub fn vault_balance(
+    vault: &AccountView,
+    fundraiser: &AccountView,
+    fundraiser_state: &Fundraiser,
+    token_program: &AccountView,
+) -> Result<u64, ProgramError> {
+    if fundraiser_state.is_native() {
+        check_lamport_vault(vault, fundraiser, fundraiser_state.vault_bump())?;
+
+        let rent = Rent::get()?.try_minimum_balance(0)?;
+        return vault
+            .lamports()
+            .checked_sub(rent)
+            .ok_or(ProgramError::InsufficientFunds);
+    }
+
+    let mint = fundraiser_state.mint_to_raise();
+    check_token_vault(vault, fundraiser, &mint, token_program, fundraiser_state.vault_bump())?;
+
+    let state = TokenAccountState::from_account_view(vault)?;
+    if state.mint != mint {
+        return Err(FundraiserError::MintMismatch.into());
+    }
+    if state.owner != *fundraiser.address() {
+        return Err(FundraiserError::VaultOwnerMismatch.into());
+    }
+    Ok(state.amount)
+}